@@ -288,7 +288,7 @@ reqwest! {
         query: common_request {
             Action: "SendSms",
         }
-        urlencoded {
+        urlencoded(rfc3986) {
             PhoneNumbers: string = join_string($phone_numbers: string[], ","),
             SignName: string = $sign_name,
             TemplateCode: string = $template_code,
@@ -822,4 +822,33 @@ reqwest! {
     }
 }
 
+// every request is signed over its sorted query, see
+// https://help.aliyun.com/zh/sdk/product-overview/rpc-mechanism
+fn patch_before_submit(
+    mut request: reqwest::Request,
+) -> Result<reqwest::Request, AliyunSmsClientError> {
+    let params = request
+        .url()
+        .query_pairs()
+        .filter(|(key, _)| key != "Signature")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    let canonical = AliyunSmsClient::canonical_query(&params);
+    let signature = sign(&format!("{}&%2F&{}", request.method(), encode(&canonical)));
+    let query = format!("{canonical}&Signature={}", encode(&signature));
+    request.url_mut().set_query(Some(&query));
+    Ok(request)
+}
+
+// base64 of the HMAC-SHA1 of `string_to_sign`, keyed by the secret key and
+// `&`, left to the hmac crate of your choice
+fn sign(string_to_sign: &str) -> String {
+    string_to_sign.to_owned()
+}
+
+// percent-encoded the way the canonical query is
+fn encode(value: &str) -> String {
+    AliyunSmsClient::canonical_query(&[(String::new(), value.to_owned())])[1..].to_owned()
+}
+
 fn main() {}
@@ -1,25 +1,57 @@
 use std::collections::HashMap;
 
 use crate::*;
-use convert_case::Case;
+use convert_case::{Case, Casing};
 use proc_macro2::{Span, TokenStream};
-use quote::{quote, ToTokens, TokenStreamExt};
-use syn::{spanned::Spanned, Ident, Path};
-use syn_prelude::{PathHelpers, ToIdent, ToIdentWithCase, ToLitStr};
+use quote::{quote, quote_spanned, ToTokens, TokenStreamExt};
+use syn::{spanned::Spanned, Attribute, Ident, LitStr, Path};
+use syn_prelude::{PathHelpers, ToIdent, ToIdentWithCase, ToLitStr, WithSuffix};
+
+// the serde traits derived for a generated struct: request data is only
+// serialized and response data only deserialized
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SerdeDirection {
+    Serialize,
+    Deserialize,
+    Both,
+}
+
+impl SerdeDirection {
+    fn serializes(self) -> bool {
+        self != Self::Deserialize
+    }
+
+    fn deserializes(self) -> bool {
+        self != Self::Serialize
+    }
+}
+
+// how nested values are flattened into `key=value` pairs
+#[derive(Clone, Copy)]
+enum KeyStyle {
+    // `filter[name]=x&tags[]=a`, for query strings
+    Bracket,
+    // `List.1.Name=x&Tags.1=a` with 1-based indexes, as rpc style apis like
+    // aliyun's expect in urlencoded bodies
+    Dotted,
+}
 
 fn make_chrono_datetime_type(span: Span) -> syn::Type {
-    let utc = syn::Path::from_idents(("chrono", "Utc", span));
-    let mut path = syn::Path::from_idents(("chrono", "DateTime", span));
-    path.push_arg(1, utc.to_type());
-    path.to_type()
+    syn::parse_quote_spanned! {span=>
+        ::power_reqwest::__private::chrono::DateTime<::power_reqwest::__private::chrono::Utc>
+    }
 }
 
 fn make_serde_json_map(span: Span) -> syn::Type {
-    let mut map = syn::Path::from_idents(("serde_json", "Map", span));
-    map.push_ident_arg(1, ("String", span).to_ident());
-    let value = syn::Path::from_idents(("serde_json", "Value", span));
-    map.push_arg(1, value.to_type());
-    map.to_type()
+    syn::parse_quote_spanned! {span=>
+        ::power_reqwest::__private::serde_json::Map<String, ::power_reqwest::__private::serde_json::Value>
+    }
+}
+
+// the derives reach serde through `power_reqwest::__private` like the rest of
+// the generated code
+fn serde_crate() -> TokenStream {
+    quote!(#[serde(crate = "::power_reqwest::__private::serde")])
 }
 
 impl ToTokens for Client {
@@ -28,7 +60,7 @@ impl ToTokens for Client {
         let param_types = self
             .options
             .as_ref()
-            .map(|params| params.gen_obj_structs())
+            .map(|params| params.gen_obj_structs(false, SerdeDirection::Both))
             .unwrap_or(vec![]);
 
         let options_arg = self
@@ -37,46 +69,563 @@ impl ToTokens for Client {
             .map(|BracedConfig { struct_name, .. }| quote! (options: #struct_name));
         let options_field = options_arg.as_ref().map(|arg| quote!(#arg,));
         let options_assign = options_arg.as_ref().map(|_| quote!(options,));
+        let timeout_field = self
+            .timeout
+            .as_ref()
+            .map(|_| quote!(timeout: std::time::Duration,));
+        let timeout_assign = self
+            .timeout
+            .as_ref()
+            .map(|millis| quote!(timeout: std::time::Duration::from_millis(#millis),));
+        let options_builder = self.options.as_ref().map(|options| options.gen_builder());
+        // every option can be left out
+        let new_default = self
+            .options
+            .as_ref()
+            .filter(|options| {
+                options
+                    .fields
+                    .iter()
+                    .all(|field| field.optional.is_some() || field.default.is_some())
+            })
+            .map(|_| {
+                quote! {
+                    pub fn new_default() -> Self {
+                        Self::new(Default::default())
+                    }
+                }
+            });
 
         let api_decls = apis.iter().map(|api| api.to_token_stream(self));
 
-        tokens.append_all(quote! {
+        let inner = if self.accept_encoding.is_some() || self.manual_decompress || self.cookie_store
+        {
+            let no_decompress = self
+                .manual_decompress
+                .then(|| quote!(.no_gzip().no_brotli().no_deflate()));
+            let cookie_store = self.cookie_store.then(|| quote!(.cookie_store(true)));
+            let default_headers = self.accept_encoding.as_ref().map(|encoding| {
+                quote! {
+                    .default_headers(::power_reqwest::__private::reqwest::header::HeaderMap::from_iter([(
+                        ::power_reqwest::__private::reqwest::header::ACCEPT_ENCODING,
+                        ::power_reqwest::__private::reqwest::header::HeaderValue::from_static(#encoding),
+                    )]))
+                }
+            });
+            // `Client::new()` panics the same way
+            quote! {
+                ::power_reqwest::__private::reqwest::Client::builder()
+                    #no_decompress
+                    #cookie_store
+                    #default_headers
+                    .build()
+                    .expect("failed to build reqwest client")
+            }
+        } else {
+            quote!(::power_reqwest::__private::reqwest::Client::new())
+        };
+
+        let validation_error = if apis.iter().any(|api| api.request.has_limits()) {
+            let error_name = self.validation_error_name();
+            Some(quote! {
+                #[derive(Clone, Debug, PartialEq)]
+                pub struct #error_name {
+                    pub field: &'static str,
+                    pub message: String,
+                }
+                impl std::fmt::Display for #error_name {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        write!(f, "invalid {}: {}", self.field, self.message)
+                    }
+                }
+                impl std::error::Error for #error_name {}
+            })
+        } else {
+            None
+        };
+
+        let page_info = if apis.iter().any(|api| {
+            api.response
+                .as_ref()
+                .is_some_and(|response| response.page_info.is_some())
+        }) {
+            let page_info_name = self.page_info_name();
+            Some(quote! {
+                #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+                pub struct #page_info_name {
+                    pub total: u64,
+                    // 1-based
+                    pub page: u64,
+                    pub size: u64,
+                }
+                impl #page_info_name {
+                    pub fn total_pages(&self) -> u64 {
+                        if self.size == 0 {
+                            0
+                        } else {
+                            self.total.div_ceil(self.size)
+                        }
+                    }
+                    pub fn has_next_page(&self) -> bool {
+                        self.page < self.total_pages()
+                    }
+                }
+            })
+        } else {
+            None
+        };
+
+        let rate_limit_info = if apis.iter().any(|api| {
+            api.response
+                .as_ref()
+                .is_some_and(|response| response.rate_limit_info.is_some())
+        }) {
+            let rate_limit_info_name = self.rate_limit_info_name();
+            Some(quote! {
+                #[derive(Clone, Debug, PartialEq, Eq)]
+                pub struct #rate_limit_info_name {
+                    pub remaining: u64,
+                    pub reset: ::power_reqwest::__private::chrono::DateTime<::power_reqwest::__private::chrono::Utc>,
+                }
+                impl #rate_limit_info_name {
+                    // `X-RateLimit-*`, or the unprefixed `RateLimit-*` headers
+                    pub fn from_headers(headers: &::power_reqwest::__private::reqwest::header::HeaderMap) -> Option<Self> {
+                        fn number(headers: &::power_reqwest::__private::reqwest::header::HeaderMap, name: &str) -> Option<u64> {
+                            headers
+                                .get(format!("x-{name}"))
+                                .or_else(|| headers.get(name))?
+                                .to_str()
+                                .ok()?
+                                .trim()
+                                .parse()
+                                .ok()
+                        }
+                        let remaining = number(headers, "ratelimit-remaining")?;
+                        let reset = number(headers, "ratelimit-reset")?;
+                        // small values count seconds from now, others are unix
+                        // timestamps
+                        let reset = if reset < 1_000_000_000 {
+                            ::power_reqwest::__private::chrono::Utc::now() + ::power_reqwest::__private::chrono::Duration::seconds(reset as i64)
+                        } else {
+                            ::power_reqwest::__private::chrono::DateTime::from_timestamp(reset as i64, 0)?
+                        };
+                        Some(Self { remaining, reset })
+                    }
+                }
+            })
+        } else {
+            None
+        };
+
+        let error_name = self.error_name();
+        let from_validation_error = validation_error.as_ref().map(|_| {
+            let validation_error_name = self.validation_error_name();
+            quote! {
+                impl From<#validation_error_name> for #error_name {
+                    fn from(err: #validation_error_name) -> Self {
+                        Self::InvalidArgument {
+                            field: err.field,
+                            message: err.message,
+                        }
+                    }
+                }
+            }
+        });
+        let error_enum = quote! {
+            #[derive(Debug)]
+            pub enum #error_name {
+                // connecting, sending or reading the response failed
+                Transport(::power_reqwest::__private::reqwest::Error),
+                // the response status is not one of the api's success status
+                Status {
+                    status: ::power_reqwest::__private::reqwest::StatusCode,
+                    body: Option<String>,
+                },
+                // the response body does not decode as the declared data
+                Decode {
+                    source: Box<dyn std::error::Error + Send + Sync>,
+                    body: Option<String>,
+                },
+                // an argument is out of its declared limits
+                InvalidArgument {
+                    field: &'static str,
+                    message: String,
+                },
+            }
+            impl std::fmt::Display for #error_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self {
+                        Self::Transport(err) => write!(f, "{err}"),
+                        Self::Status { status, .. } => write!(f, "unexpected status {status}"),
+                        Self::Decode { source, .. } => write!(f, "failed to decode response: {source}"),
+                        Self::InvalidArgument { field, message } => {
+                            write!(f, "invalid {field}: {message}")
+                        }
+                    }
+                }
+            }
+            impl std::error::Error for #error_name {
+                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                    match self {
+                        Self::Transport(err) => Some(err),
+                        Self::Decode { source, .. } => Some(source.as_ref()),
+                        _ => None,
+                    }
+                }
+            }
+            impl From<::power_reqwest::__private::reqwest::Error> for #error_name {
+                fn from(err: ::power_reqwest::__private::reqwest::Error) -> Self {
+                    Self::Transport(err)
+                }
+            }
+            #from_validation_error
+        };
+
+        // the shared error type must take transport errors, and the errors
+        // raised by the generated methods
+        let error_type_check = self.error_type.as_ref().map(|error_type| {
+            quote! {
+                const _: fn() = || {
+                    fn requires_from_errors<E: From<::power_reqwest::__private::reqwest::Error> + From<#error_name>>() {}
+                    requires_from_errors::<#error_type>();
+                };
+            }
+        });
+
+        let debug_request = self.debug_requests.then(|| {
+            quote! {
+                // `secrets` are the keys of masked query pairs, headers and
+                // json or urlencoded body fields
+                fn debug_request(request: &::power_reqwest::__private::reqwest::Request, secrets: &[&str]) {
+                    const MASK: &str = "***";
+                    let is_secret = |key: &str| secrets.iter().any(|secret| secret.eq_ignore_ascii_case(key));
+                    let mask_pairs = |pairs: ::power_reqwest::__private::reqwest::Url| {
+                        pairs
+                            .query_pairs()
+                            .map(|(key, value)| {
+                                let value = if is_secret(&key) { MASK.into() } else { value };
+                                (key.into_owned(), value.into_owned())
+                            })
+                            .collect::<Vec<_>>()
+                    };
+                    let mut url = request.url().clone();
+                    if url.query().is_some() {
+                        let pairs = mask_pairs(url.clone());
+                        url.query_pairs_mut().clear().extend_pairs(pairs);
+                    }
+                    eprintln!("{} {}", request.method(), url);
+                    for (name, value) in request.headers() {
+                        let value = if is_secret(name.as_str())
+                            || name == ::power_reqwest::__private::reqwest::header::AUTHORIZATION
+                            || name == ::power_reqwest::__private::reqwest::header::PROXY_AUTHORIZATION
+                            || name == ::power_reqwest::__private::reqwest::header::COOKIE
+                        {
+                            MASK
+                        } else {
+                            value.to_str().unwrap_or("<binary>")
+                        };
+                        eprintln!("{name}: {value}");
+                    }
+                    let Some(body) = request.body().and_then(|body| body.as_bytes()) else {
+                        return;
+                    };
+                    let body = String::from_utf8_lossy(body);
+                    let content_type = request
+                        .headers()
+                        .get(::power_reqwest::__private::reqwest::header::CONTENT_TYPE)
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default();
+                    let body = if content_type.starts_with("application/json") {
+                        fn mask(value: &mut ::power_reqwest::__private::serde_json::Value, is_secret: &dyn Fn(&str) -> bool) {
+                            match value {
+                                ::power_reqwest::__private::serde_json::Value::Object(map) => {
+                                    for (key, value) in map.iter_mut() {
+                                        if is_secret(key) {
+                                            *value = MASK.into();
+                                        } else {
+                                            mask(value, is_secret);
+                                        }
+                                    }
+                                }
+                                ::power_reqwest::__private::serde_json::Value::Array(items) => {
+                                    items.iter_mut().for_each(|item| mask(item, is_secret))
+                                }
+                                _ => {}
+                            }
+                        }
+                        match ::power_reqwest::__private::serde_json::from_str::<::power_reqwest::__private::serde_json::Value>(&body) {
+                            Ok(mut json) => {
+                                mask(&mut json, &is_secret);
+                                json.to_string()
+                            }
+                            Err(_) => body.into_owned(),
+                        }
+                    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+                        let mut pairs = ::power_reqwest::__private::reqwest::Url::parse("http://localhost/").expect("valid url");
+                        pairs.set_query(Some(&body));
+                        let pairs = mask_pairs(pairs);
+                        let mut url = ::power_reqwest::__private::reqwest::Url::parse("http://localhost/").expect("valid url");
+                        url.query_pairs_mut().extend_pairs(pairs);
+                        url.query().unwrap_or_default().to_owned()
+                    } else {
+                        body.into_owned()
+                    };
+                    eprintln!("\n{body}");
+                }
+            }
+        });
+
+        let base_urls = (!self.base_urls.is_empty()).then(|| {
+            let base_urls = &self.base_urls;
+            quote!(const BASE_URLS: &'static [&'static str] = &[#(#base_urls),*];)
+        });
+
+        // only clients signing their requests in an `on_submit` hook need it
+        let canonical_query = self
+            .hooks
+            .as_ref()
+            .is_some_and(|hooks| hooks.on_submit.is_some())
+            .then(|| {
+                quote! {
+                    // `k=v` pairs percent-encoded as RFC 3986 and sorted by key, the
+                    // string to sign in rpc style schemes like aliyun's
+                    pub fn canonical_query(params: &[(String, String)]) -> String {
+                        fn encode(s: &str) -> String {
+                            let mut encoded = String::with_capacity(s.len());
+                            for b in s.bytes() {
+                                match b {
+                                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                                        encoded.push(b as char)
+                                    }
+                                    _ => encoded.push_str(&format!("%{b:02X}")),
+                                }
+                            }
+                            encoded
+                        }
+                        let mut pairs = params
+                            .iter()
+                            .map(|(k, v)| (encode(k), encode(v)))
+                            .collect::<Vec<_>>();
+                        pairs.sort();
+                        pairs
+                            .into_iter()
+                            .map(|(k, v)| format!("{k}={v}"))
+                            .collect::<Vec<_>>()
+                            .join("&")
+                    }
+                }
+            });
+
+        // read by every api of a `decompress: manual` client before the body
+        // is decoded
+        let decompress = self.manual_decompress.then(|| {
+            quote! {
+                // the body decoded as its `Content-Encoding` says, gzip is also
+                // recognized by its magic bytes when the server does not say
+                fn decompress(
+                    headers: &::power_reqwest::__private::reqwest::header::HeaderMap,
+                    body: ::power_reqwest::__private::bytes::Bytes,
+                ) -> std::io::Result<::power_reqwest::__private::bytes::Bytes> {
+                    use std::io::Read;
+                    let encoding = headers
+                        .get(::power_reqwest::__private::reqwest::header::CONTENT_ENCODING)
+                        .and_then(|value| value.to_str().ok())
+                        .map(|value| value.trim().to_ascii_lowercase());
+                    let mut decoded = Vec::new();
+                    match encoding.as_deref() {
+                        Some("gzip" | "x-gzip") => {
+                            ::power_reqwest::__private::flate2::read::MultiGzDecoder::new(&body[..])
+                                .read_to_end(&mut decoded)?;
+                        }
+                        Some("deflate") => {
+                            ::power_reqwest::__private::flate2::read::ZlibDecoder::new(&body[..])
+                                .read_to_end(&mut decoded)?;
+                        }
+                        Some("br") => {
+                            ::power_reqwest::__private::brotli_decompressor::Decompressor::new(&body[..], 4096)
+                                .read_to_end(&mut decoded)?;
+                        }
+                        None | Some("identity") if body.starts_with(&[0x1f, 0x8b]) => {
+                            ::power_reqwest::__private::flate2::read::MultiGzDecoder::new(&body[..])
+                                .read_to_end(&mut decoded)?;
+                        }
+                        _ => return Ok(body),
+                    }
+                    Ok(decoded.into())
+                }
+            }
+        });
+
+        // built on the first blocking call and never dropped, so pooled
+        // connections outlive each call
+        let blocking_runtime = apis.iter().any(|api| api.blocking.is_some()).then(|| {
+            quote! {
+                fn blocking_runtime() -> &'static ::power_reqwest::__private::tokio::runtime::Runtime {
+                    static RUNTIME: std::sync::OnceLock<::power_reqwest::__private::tokio::runtime::Runtime> =
+                        std::sync::OnceLock::new();
+                    RUNTIME.get_or_init(|| {
+                        ::power_reqwest::__private::tokio::runtime::Builder::new_current_thread()
+                            .enable_all()
+                            .build()
+                            .expect("failed to build tokio runtime")
+                    })
+                }
+            }
+        });
+
+        // read by the retry loop, on the retried status codes
+        let retry_after = self.retry.as_ref().map(|_| {
+            quote! {
+                // the delay asked by a `Retry-After` header, given either as
+                // seconds or as an http date
+                pub fn retry_after(headers: &::power_reqwest::__private::reqwest::header::HeaderMap) -> Option<std::time::Duration> {
+                    let value = headers.get(::power_reqwest::__private::reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+                    if let Ok(seconds) = value.parse::<u64>() {
+                        return Some(std::time::Duration::from_secs(seconds));
+                    }
+                    // `Sun, 06 Nov 1994 08:49:37 GMT`, a date already passed asks
+                    // for no delay
+                    let at = ::power_reqwest::__private::chrono::DateTime::parse_from_rfc2822(value).ok()?;
+                    Some(
+                        (at.with_timezone(&::power_reqwest::__private::chrono::Utc) - ::power_reqwest::__private::chrono::Utc::now())
+                            .to_std()
+                            .unwrap_or_default(),
+                    )
+                }
+            }
+        });
+
+        let mut expanded = quote! {
             #(#param_types)*
+            #options_builder
+            #validation_error
+            #error_enum
+            #page_info
+            #rate_limit_info
+            #error_type_check
 
             pub struct #name {
                 #options_field
-                inner: reqwest::Client,
+                #timeout_field
+                inner: ::power_reqwest::__private::reqwest::Client,
             }
 
             impl #name {
+                #base_urls
+                #debug_request
+
                 pub fn new(#options_arg) -> Self {
+                    Self::with_client(#inner, #options_assign)
+                }
+
+                // a preconfigured client, e.g. with a proxy or custom tls; the
+                // `accept_encoding`, `decompress` and `cookies` options are
+                // left to it
+                pub fn with_client(inner: ::power_reqwest::__private::reqwest::Client, #options_arg) -> Self {
                     Self {
                         #options_assign
-                        inner: reqwest::Client::new(),
+                        #timeout_assign
+                        inner,
                     }
                 }
+
+                #new_default
+
+                #canonical_query
+                #decompress
+                #blocking_runtime
+                #retry_after
             }
 
             #(#api_decls)*
-        })
+        };
+
+        let allow_dead_code = self.allow_dead_code.then(|| quote!(#[allow(dead_code)]));
+        if let Some(module) = &self.module {
+            // names around the invocation, like hook fns, stay in reach
+            expanded = quote! {
+                #allow_dead_code
+                pub mod #module {
+                    #[allow(unused_imports)]
+                    use super::*;
+                    #expanded
+                }
+            };
+        } else if let Some(allow_dead_code) = allow_dead_code {
+            // the lint is allowed for everything in a hidden module, whose
+            // items are used from where the macro is invoked
+            let module = (
+                format!("__{}", name.to_string().to_case(Case::Snake)),
+                name.span(),
+            )
+                .to_ident();
+            expanded = quote! {
+                #allow_dead_code
+                #[doc(hidden)]
+                mod #module {
+                    #[allow(unused_imports)]
+                    use super::*;
+                    #expanded
+                }
+                #[allow(unused_imports)]
+                pub use #module::*;
+            };
+        }
+        tokens.append_all(expanded);
+
+        // there are no warnings for proc macros on stable, a deprecated item
+        // used at the api's span stands in for one
+        if let (Some(api), Some(max)) = (&self.too_many_apis, &self.max_apis) {
+            let note = format!(
+                "more than {} apis in one `reqwest!`, consider splitting the client into several invocations",
+                max.base10_digits()
+            );
+            let used = quote_spanned!(api.span()=> TooManyApis);
+            tokens.append_all(quote! {
+                const _: () = {
+                    #[deprecated(note = #note)]
+                    struct TooManyApis;
+                    let _ = #used;
+                };
+            });
+        }
+    }
+}
+
+impl Client {
+    fn error_name(&self) -> Ident {
+        self.type_affix.apply(self.name.with_suffix("Error"))
+    }
+
+    // `.into()` for errors of the generated enum, when methods return the
+    // shared `error_type`
+    fn error_into(&self) -> Option<TokenStream> {
+        self.error_type.as_ref().map(|_| quote!(.into()))
+    }
+
+    fn validation_error_name(&self) -> Ident {
+        self.type_affix
+            .apply(self.name.with_suffix("ValidationError"))
+    }
+
+    fn page_info_name(&self) -> Ident {
+        self.type_affix.apply(self.name.with_suffix("PageInfo"))
+    }
+
+    fn rate_limit_info_name(&self) -> Ident {
+        self.type_affix
+            .apply(self.name.with_suffix("RateLimitInfo"))
     }
 }
 
 impl Type {
-    fn to_type(&self) -> syn::Type {
+    pub(crate) fn to_type(&self) -> syn::Type {
         match self {
             Self::Constant(c) => c.infer_type(),
             Self::String(s) => Path::from_ident(("String", s.span)).to_type(),
             Self::Bool(span) => Path::from_ident(("bool", *span)).to_type(),
-            Self::Integer(i) => {
-                if i.token.eq("uint") {
-                    Path::from_ident(("u64", i.token.span())).to_type()
-                } else if i.token.eq("int") || i.token.eq("integer") {
-                    Path::from_ident(("i64", i.token.span())).to_type()
-                } else {
-                    Path::from_ident(&i.token).to_type()
-                }
-            }
+            // bare keywords are resolved with `default_int_width` when parsing
+            Self::Integer(i) => Path::from_ident(&i.token).to_type(),
             Self::Float(f) => {
                 if f.token.eq("float") {
                     Path::from_ident(("f64", f.token.span())).to_type()
@@ -88,6 +637,8 @@ impl Type {
             Self::Datetime(d) => make_chrono_datetime_type(d.span),
             Self::JsonText(j) => Path::from_ident(("String", j.span)).to_type(),
             Self::Map(span) => make_serde_json_map(*span),
+            Self::Enum(e) => syn::Path::from_ident(&e.enum_name).to_type(),
+            Self::File(file) => syn::parse_quote_spanned!(file.span=> (String, Vec<u8>)),
             Self::List(l) => {
                 let mut path = Path::from_ident(("Vec", l.bracket.span.close()));
                 path.push_arg(0, l.element_type.to_type());
@@ -102,21 +653,123 @@ impl Constant {
         match self {
             Self::String(value) => Path::from_ident(("String", value.span())).to_type(),
             Self::Bool(value) => Path::from_ident(("bool", value.span())).to_type(),
-            Self::Int(value) => Path::from_ident(("u64", value.span())).to_type(),
-            Self::Float(value) => Path::from_ident(("f64", value.span())).to_type(),
-            Self::Object(_obj) => {
-                todo!("not support object constant yet")
+            Self::Int(value) => {
+                if self.is_negative() {
+                    Path::from_ident(("i64", value.span())).to_type()
+                } else {
+                    Path::from_ident(("u64", value.span())).to_type()
+                }
             }
+            Self::Float(value) => Path::from_ident(("f64", value.span())).to_type(),
+            // a json object, as declared by the `object` type
+            Self::Object(obj) => make_serde_json_map(obj.span),
             Self::Array(arr) => {
-                let el = arr.elements.first().unwrap();
+                // nothing to infer from `[]`, any json value fits
+                let element = arr
+                    .elements
+                    .iter()
+                    .find(|el| !matches!(el, Self::Array(arr) if arr.elements.is_empty()))
+                    .or(arr.elements.first());
+                let element_type = match element {
+                    Some(el) => el.infer_type(),
+                    None => syn::parse_quote!(::power_reqwest::__private::serde_json::Value),
+                };
                 let mut path = Path::from_ident(("Vec", arr.span));
-                path.push_arg(0, el.infer_type());
+                path.push_arg(0, element_type);
                 path.to_type()
             }
         }
     }
 }
 
+impl Constant {
+    // the constant as written in the macro, for doc comments
+    fn to_doc(&self) -> String {
+        match self {
+            Self::String(s) => format!("{:?}", s.value()),
+            Self::Bool(b) => b.value.to_string(),
+            Self::Int(i) => i.base10_digits().to_owned(),
+            Self::Float(f) => f.base10_digits().to_owned(),
+            Self::Object(ObjectConstant { fields, .. }) => format!(
+                "{{ {} }}",
+                fields
+                    .iter()
+                    .map(|f| format!("{}: {}", f.name, f.value.to_doc()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Array(ConstantArray { elements, .. }) => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|e| e.to_doc())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl Type {
+    // object constants of a declared object are built as its struct, the
+    // fields left out keep their defaults
+    fn gen_constant(&self, c: &Constant) -> TokenStream {
+        match (self, c) {
+            (Self::Object(obj), Constant::Object(ObjectConstant { fields, .. })) => {
+                let struct_name = &obj.struct_name;
+                let inits = fields
+                    .iter()
+                    .filter_map(|ObjectConstantField { name, value }| {
+                        let field = obj.field_of_key(name)?;
+                        let field_name = &field.field_name;
+                        let mut value = match (&field.typ, value) {
+                            (_, Constant::String(lit)) if field.cow => {
+                                quote!(std::borrow::Cow::Borrowed(#lit))
+                            }
+                            (Some(typ), value) => typ.gen_constant(value),
+                            (None, value) => value.to_token_stream(),
+                        };
+                        if field.optional.is_some() {
+                            value = quote!(Some(#value));
+                        }
+                        Some(quote!(#field_name: #value,))
+                    });
+                quote!(#struct_name {
+                    #(#inits)*
+                    ..Default::default()
+                })
+            }
+            (
+                Self::List(ListType { element_type, .. }),
+                Constant::Array(ConstantArray { elements, .. }),
+            ) => {
+                let elements = elements.iter().map(|el| element_type.gen_constant(el));
+                quote!(vec![#(#elements),*])
+            }
+            _ => c.to_token_stream(),
+        }
+    }
+}
+
+impl Constant {
+    // a `serde_json::Value` of the constant, for the values of object constants
+    fn to_json_value(&self) -> TokenStream {
+        match self {
+            Self::String(s) => quote!(::power_reqwest::__private::serde_json::Value::from(#s)),
+            Self::Bool(b) => quote!(::power_reqwest::__private::serde_json::Value::from(#b)),
+            Self::Int(i) => quote!(::power_reqwest::__private::serde_json::Value::from(#i)),
+            Self::Float(f) => quote!(::power_reqwest::__private::serde_json::Value::from(#f)),
+            Self::Object(_) => quote!(::power_reqwest::__private::serde_json::Value::Object(#self)),
+            Self::Array(ConstantArray { elements, .. }) => {
+                let elements = elements.iter().map(|el| el.to_json_value());
+                quote!(::power_reqwest::__private::serde_json::Value::Array(
+                    vec![#(#elements),*]
+                ))
+            }
+        }
+    }
+}
+
 impl ToTokens for Constant {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         tokens.append_all(match self {
@@ -124,8 +777,16 @@ impl ToTokens for Constant {
             Self::Bool(b) => quote!(#b),
             Self::Int(i) => quote!(#i),
             Self::Float(f) => quote!(#f),
-            Self::Object(_) => todo!(),
-            Self::Array(ConstantArray { elements, .. }) => quote!(#(#elements),*),
+            Self::Object(ObjectConstant { fields, .. }) => {
+                let keys = fields.iter().map(|field| field.name.to_lit_str());
+                let values = fields.iter().map(|field| field.value.to_json_value());
+                quote!({
+                    let mut map = ::power_reqwest::__private::serde_json::Map::new();
+                    #(map.insert(#keys.to_owned(), #values);)*
+                    map
+                })
+            }
+            Self::Array(ConstantArray { elements, .. }) => quote!(vec![#(#elements),*]),
         })
     }
 }
@@ -144,174 +805,1853 @@ impl Api {
         } = self;
 
         let mut types = if let Some(data) = &request.data {
-            data.data.gen_obj_structs()
+            if let Some(data_match) = &data.data_match {
+                data_match.gen_types()
+            } else {
+                data.data.gen_obj_structs(false, SerdeDirection::Serialize)
+            }
         } else {
             vec![]
         };
         if let Some(queries) = &request.query {
-            types.extend(queries.gen_obj_structs());
+            types.extend(queries.gen_obj_structs(false, SerdeDirection::Serialize));
         }
         if let Some(headers) = &request.header {
-            types.extend(headers.gen_obj_structs());
+            types.extend(headers.gen_obj_structs(false, SerdeDirection::Serialize));
+        }
+        let error_name = client.validation_error_name();
+        for config in request.configs() {
+            types.extend(config.gen_validate(&error_name));
         }
 
         if let Some(response) = response {
             if let Some(data) = &response.data {
-                types.extend(data.data.gen_obj_structs());
+                types.extend(data.data.gen_obj_structs(true, SerdeDirection::Deserialize));
+                types.extend(data.gen_decoder());
+                if let Some(page_info) = &response.page_info {
+                    types.push(page_info.gen_accessor(data, &client.page_info_name()));
+                }
             }
             if let Some(cookies) = &response.cookie {
-                types.extend(cookies.gen_obj_structs());
+                types.extend(cookies.gen_obj_structs(false, SerdeDirection::Deserialize));
             }
             if let Some(headers) = &response.header {
-                types.extend(headers.gen_obj_structs());
+                types.extend(headers.gen_obj_structs(false, SerdeDirection::Deserialize));
             }
         }
 
+        // plain string arguments take `impl Into<String>`, so callers can pass
+        // `&str` literals; optional ones stay `Option<String>` to keep `None`
+        // free of type annotations
+        let into_strings = variables
+            .iter()
+            .filter(|var| var.is_required_string())
+            .map(|Variable { name, .. }| quote!(let #name: String = #name.into();))
+            .collect::<Vec<_>>();
         let mut args = variables
             .iter()
-            .map(|Variable { name, typ, .. }| {
-                if let Some(typ) = &typ {
-                    let typ = typ.to_type();
-                    quote!(#name: #typ)
+            .map(|var| {
+                if var.is_required_string() {
+                    let name = &var.name;
+                    quote!(#name: impl Into<String>)
                 } else {
-                    quote!(#name: String)
+                    var.gen_arg()
                 }
             })
             .collect::<Vec<_>>();
-        args.insert(0, quote! {&self});
-
-        let method = self.method.to_ident_with_case(Case::Upper);
-        let url = self.uri.gen_url_format_expr(&client.option_map);
-        let basic_auth = self.uri.gen_basic_auth();
-
-        quote! {
-            #(#types)*
-
-            impl #client_name {
-                pub async fn #name(#(#args),*) {
-                    let mut req = self.inner.request(reqwest::Method::#method, #url);
-                    #basic_auth
-                }
-            }
-        }
-    }
-}
-
-impl ApiUri {
-    fn gen_url_format_expr(&self, options: &HashMap<Ident, Field>) -> TokenStream {
-        let Self {
-            uri_format,
-            uri_variables,
+        if let Some(ApiRequestData {
+            data: BracedConfig { struct_name, .. },
+            data_var: Some(data_var),
+            optional,
+            array,
             ..
-        } = self;
-        if uri_variables.is_empty() {
-            uri_format.to_token_stream()
-        } else {
-            let args = uri_variables.iter().map(
-                |Variable {
-                     name,
-                     client_option,
-                     ..
-                 }| {
-                    if *client_option {
-                        let opt = options.get(name).unwrap();
-                        if opt.optional.is_some() {
-                            if let Some(default) = &opt.default {
-                                quote!(self.options.#name.as_ref().map(|v|v.to_owned()).unwrap_or(#default))
-                            } else {
-                                quote!(self.options.#name.as_ref().map(|v|v.to_owned()).unwrap_or_default())
-                            }
-                        } else {
-                            quote!(&self.options.#name)
-                        }
-                    } else {
-                        quote!(&#name)
-                    }
-                },
-            );
-            quote!(format!(#uri_format, #(#args),*))
-        }
-    }
-    fn gen_basic_auth(&self) -> Option<TokenStream> {
-        let Self { user, passwd, .. } = self;
-        if let Some(user) = user {
-            let pass = if let Some(passwd) = passwd {
-                quote!(Some(#passwd))
+        }) = &request.data
+        {
+            let data_type = if array.is_some() {
+                quote!(Vec<#struct_name>)
             } else {
-                quote!(None)
+                quote!(#struct_name)
             };
-            Some(quote!(req = req.basic_auth(#user, #pass)))
+            if optional.is_some() {
+                args.push(quote!(#data_var: Option<#data_type>));
+            } else {
+                args.push(quote!(#data_var: #data_type));
+            }
+        }
+        if let Some(DataMatch { var, enum_name, .. }) = request
+            .data
+            .as_ref()
+            .and_then(|data| data.data_match.as_ref())
+        {
+            args.push(quote!(#var: #enum_name));
+        }
+        if let (Some(BracedConfig { struct_name, .. }), Some(query_var)) =
+            (&request.query, &request.query_var)
+        {
+            args.push(quote!(#query_var: #struct_name));
+        }
+        if let (Some(BracedConfig { struct_name, .. }), Some(header_var)) =
+            (&request.header, &request.header_var)
+        {
+            args.push(quote!(#header_var: #struct_name));
+        }
+        if let Some(BytesBody { var, .. }) = &request.bytes_body {
+            args.push(quote!(#var: ::power_reqwest::__private::bytes::Bytes));
+        }
+        let arg_check = self.gen_arg_check(client);
+        // verbatim names like `AddSmsSign` are declared on purpose
+        let allow_case = client
+            .verbatim_method_names
+            .then(|| quote!(#[allow(non_snake_case)]));
+        // a head response has no body to decode, whatever is declared
+        let (output, decode) = if self.method.eq("head") {
+            (
+                quote!(::power_reqwest::__private::reqwest::header::HeaderMap),
+                quote!(Ok(response.headers().clone())),
+            )
         } else {
-            None
+            Self::gen_output(response.as_ref(), client)
+        };
+        let error_type = client
+            .error_type
+            .as_ref()
+            .map(|error_type| error_type.to_token_stream())
+            .unwrap_or(client.error_name().to_token_stream());
+        let mut arg_names = variables.iter().map(|var| &var.name).collect::<Vec<_>>();
+        if let Some(data) = &request.data {
+            arg_names.extend(data.data_var.iter());
+            arg_names.extend(data.data_match.iter().map(|data_match| &data_match.var));
         }
-    }
-}
-
-fn make_object_struct(name: &Ident, fields: &Vec<Field>) -> TokenStream {
-    let fields_in_struct = fields.iter().map(
-        |Field {
-             name,
-             field_name,
-             optional,
-             typ,
-             ..
-         }| {
-            let mut field_type = if let Some(typ) = typ {
-                typ.to_type()
+        arg_names.extend(request.query.as_ref().and(request.query_var.as_ref()));
+        arg_names.extend(request.header.as_ref().and(request.header_var.as_ref()));
+        arg_names.extend(request.bytes_body.iter().map(|body| &body.var));
+        let blocking_fn = self.blocking.map(|_| {
+            let fn_name = name.with_suffix("_blocking");
+            quote! {
+                #allow_case
+                pub fn #fn_name(&self, #(#args),*) -> Result<#output, #error_type> {
+                    Self::blocking_runtime().block_on(self.#name(#(#arg_names),*))
+                }
+            }
+        });
+        let stream_fn = response.as_ref().and_then(|response| {
+            let data = response.data.as_ref()?;
+            let PageStream { items, next } = response.page_info.as_ref()?.stream.as_ref()?;
+            let fn_name = name.with_suffix("_stream");
+            let items_field = data.data.fields.iter().find(|field| field.field_name.eq(items))?;
+            let Some(Type::List(ListType { element_type, .. })) = &items_field.typ else {
+                return None;
+            };
+            let item_type = element_type.to_type();
+            // back to the decoded data from the output of the api method
+            let rate_limit_info = response
+                .rate_limit_info
+                .map(|_| quote!(let (data, _) = data;));
+            let optional = response
+                .optional
+                .map(|_| quote!(let data = data?;));
+            // the data was just decoded, nothing else holds it
+            let arc = client
+                .response_arc
+                .then(|| quote!(let data = std::sync::Arc::try_unwrap(data).ok()?;));
+            let page_items = if items_field.optional.is_some() {
+                quote!(data.#items.unwrap_or_default())
             } else {
-                syn::Path::from_ident(("String", name.span())).to_type()
+                quote!(data.#items)
             };
-            if optional.is_some() {
-                let mut option = syn::Path::from_ident(("Option", field_type.span()));
-                option.push_arg(0, field_type);
-                field_type = option.to_type()
-            }
-
-            let mut serde_options = None;
-            if !name.value().eq(&field_name.to_string()) {
-                serde_options = Some(vec![quote! {rename = #name}])
-            }
-
-            if let Some(Type::Datetime(DateTimeType {
-                format: Some(DateTimeFormat { mod_name, .. }),
-                ..
-            })) = typ
-            {
-                let formatter = mod_name.to_lit_str();
-                if let Some(options) = serde_options.as_mut() {
-                    options.push(quote! {with = #formatter})
+            // the other arguments are kept in the state, cloned for every page
+            let others = arg_names
+                .iter()
+                .filter(|arg| !next.eq(**arg))
+                .collect::<Vec<_>>();
+            let call_args = arg_names.iter().map(|arg| {
+                if next.eq(*arg) {
+                    quote!(#arg)
                 } else {
-                    serde_options = Some(vec![quote! {with = #formatter}]);
+                    quote!(#arg.clone())
                 }
-            };
-            let serde = serde_options.map(|opts| quote! {#[serde(#(#opts),*)]});
-
-            quote! {
-                #serde
-                pub #field_name: #field_type
+            });
+            Some(quote! {
+                #allow_case
+                pub fn #fn_name(
+                    &self,
+                    #(#args),*
+                ) -> impl ::power_reqwest::__private::futures_util::Stream<Item = Result<#item_type, #error_type>> + '_ {
+                    #(#into_strings)*
+                    // the items of a page are handed out before the next page
+                    // is fetched, until the page info has no next page. the
+                    // state is named apart from the arguments
+                    ::power_reqwest::__private::futures_util::stream::unfold(
+                        (Some(#next), Vec::new().into_iter(), (#(#others,)*)),
+                        move |(mut stream_pages, mut stream_items, (#(#others,)*))| async move {
+                            loop {
+                                if let Some(item) = stream_items.next() {
+                                    return Some((Ok(item), (stream_pages, stream_items, (#(#others,)*))));
+                                }
+                                let #next = stream_pages.take()?;
+                                match self.#name(#(#call_args),*).await {
+                                    Ok(data) => {
+                                        #rate_limit_info
+                                        #optional
+                                        #arc
+                                        let info = data.page_info();
+                                        if info.has_next_page() {
+                                            stream_pages = (info.page + 1).try_into().ok();
+                                        }
+                                        stream_items = #page_items.into_iter();
+                                    }
+                                    Err(err) => {
+                                        return Some((Err(err), (None, Vec::new().into_iter(), (#(#others,)*))));
+                                    }
+                                }
+                            }
+                        },
+                    )
+                }
+            })
+        });
+        args.insert(0, quote! {&self});
+
+        let method = self.method.to_ident_with_case(Case::Upper);
+        let based = self.uri.schema.is_none() && !client.base_urls.is_empty();
+        let new_request = self.uri.gen_new_request(&method, &client.option_map, based);
+        let timeout = client
+            .timeout
+            .as_ref()
+            .map(|_| quote!(req = req.timeout(self.timeout);));
+        let basic_auth = if let Some(auth) = &request.auth {
+            Some(auth.gen_basic_auth(&client.option_map))
+        } else {
+            self.uri.gen_basic_auth()
+        };
+        let accept = request
+            .accept
+            .as_ref()
+            .map(|accept| quote!(req = req.header(::power_reqwest::__private::reqwest::header::ACCEPT, #accept);));
+        let query = request.gen_query(&client.option_map);
+        let header = request.gen_header(&client.option_map);
+        let body = if let Some(BytesBody { var, .. }) = &request.bytes_body {
+            Some(match &request.transfer {
+                Some(transfer) => transfer.gen_body(var),
+                None => quote!(req = req.body(#var);),
+            })
+        } else {
+            request
+                .data
+                .as_ref()
+                .map(|data| data.gen_body(&client.option_map))
+        };
+        let key_fn = request.key.as_ref().map(|key| {
+            let key_fn = key.gen_key_fn(name, variables);
+            quote!(#allow_case #key_fn)
+        });
+        let success_fn = response
+            .as_ref()
+            .and_then(|response| response.success_status.as_ref())
+            .map(|success_status| {
+                let success_fn = success_status.gen_success_fn(name);
+                quote!(#allow_case #success_fn)
+            });
+
+        let execute = if based {
+            // the built request is replayed against the next base url when
+            // connecting fails, unless its body is a stream
+            quote! {{
+                let mut fallbacks = Self::BASE_URLS[1..].iter();
+                loop {
+                    let retry = request.try_clone();
+                    match self.inner.execute(request).await {
+                        Err(err) if err.is_connect() => {
+                            let next = fallbacks
+                                .by_ref()
+                                .find_map(|base| ::power_reqwest::__private::reqwest::Url::parse(&format!("{base}{path}")).ok());
+                            if let (Some(url), Some(retry)) = (next, retry) {
+                                request = retry;
+                                *request.url_mut() = url;
+                                continue;
+                            }
+                            break Err(err);
+                        }
+                        result => break result,
+                    }
+                }
+            }}
+        } else {
+            quote!(self.inner.execute(request).await)
+        };
+        let on_submit = client
+            .hooks
+            .as_ref()
+            .and_then(|hooks| hooks.on_submit.as_ref())
+            .map(|hook| quote!(request = #hook(request)?;));
+        // every header of the built request, accept and basic auth included,
+        // so a signing hook sees them in the order they are sent
+        let sort_headers = client.sort_headers.then(|| {
+            quote! {
+                let mut sorted = request
+                    .headers()
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect::<Vec<_>>();
+                sorted.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+                let headers = request.headers_mut();
+                headers.clear();
+                for (name, value) in sorted {
+                    headers.append(name, value);
+                }
+            }
+        });
+        let build =
+            (on_submit.is_some() || sort_headers.is_some() || client.retry.is_some() || based)
+                .then(|| {
+                    quote! {
+                        let mut request = req.build()?;
+                        #sort_headers
+                        #on_submit
+                    }
+                });
+        let send = if let Some(RetryPolicy {
+            attempts,
+            delay,
+            status,
+            ..
+        }) = &client.retry
+        {
+            // a streamed body can't be sent again, its result is kept
+            quote! {
+                #build
+                let mut attempt = 0u32;
+                let response = loop {
+                    let again = request.try_clone();
+                    let result = #execute;
+                    attempt += 1;
+                    let wait = match &result {
+                        Err(_) => None,
+                        Ok(response) if [#(#status),*].contains(&response.status().as_u16()) => {
+                            Self::retry_after(response.headers())
+                        }
+                        Ok(_) => break result,
+                    };
+                    match again {
+                        Some(again) if attempt < #attempts => {
+                            let backoff = std::time::Duration::from_millis(#delay)
+                                .saturating_mul(2u32.saturating_pow(attempt - 1));
+                            ::power_reqwest::__private::tokio::time::sleep(wait.unwrap_or(backoff)).await;
+                            request = again;
+                        }
+                        _ => break result,
+                    }
+                }?;
+            }
+        } else if build.is_some() {
+            quote! {
+                #build
+                let response = #execute?;
+            }
+        } else {
+            quote!(let response = req.send().await?;)
+        };
+        let on_response = client
+            .hooks
+            .as_ref()
+            .and_then(|hooks| hooks.on_response.as_ref())
+            .map(|hook| quote!(let response = #hook(response)?;));
+        let status_check = {
+            let error_name = client.error_name();
+            let into = client.error_into();
+            let is_success = if response
+                .as_ref()
+                .is_some_and(|response| response.success_status.is_some())
+            {
+                let success_fn = name.with_suffix("_is_success");
+                quote!(Self::#success_fn(status))
+            } else {
+                quote!(status.is_success())
+            };
+            let body = if client.capture_body_on_error && client.manual_decompress {
+                quote!({
+                    let headers = response.headers().clone();
+                    match response.bytes().await {
+                        Ok(body) => Self::decompress(&headers, body)
+                            .ok()
+                            .map(|body| String::from_utf8_lossy(&body).into_owned()),
+                        Err(_) => None,
+                    }
+                })
+            } else if client.capture_body_on_error {
+                quote!(response.text().await.ok())
+            } else {
+                quote!(None)
+            };
+            quote! {
+                let status = response.status();
+                if !#is_success {
+                    let body = #body;
+                    return Err(#error_name::Status { status, body }#into);
+                }
+            }
+        };
+
+        let debug = client.debug_requests.then(|| {
+            let secrets = request.secret_keys(&client.option_map);
+            quote! {
+                if cfg!(debug_assertions) {
+                    if let Some(request) = req.try_clone().and_then(|req| req.build().ok()) {
+                        Self::debug_request(&request, &[#(#secrets),*]);
+                    }
+                }
+            }
+        });
+
+        let smoke_test = client.emit_tests.then(|| request.gen_smoke_test(name));
+
+        quote! {
+            #(#types)*
+            #smoke_test
+
+            impl #client_name {
+                #allow_case
+                pub async fn #name(#(#args),*) -> Result<#output, #error_type> {
+                    #arg_check
+                    #(#into_strings)*
+                    #new_request
+                    #timeout
+                    #basic_auth
+                    #accept
+                    #header
+                    #query
+                    #body
+                    #debug
+                    #send
+                    #on_response
+                    #status_check
+                    #decode
+                }
+
+                #blocking_fn
+                #stream_fn
+
+                #key_fn
+                #success_fn
+            }
+        }
+    }
+}
+
+impl Api {
+    // declared limits of the arguments, checked before anything is built
+    fn gen_arg_check(&self, client: &Client) -> Option<TokenStream> {
+        let error_name = &client.validation_error_name();
+        let mut names = vec![];
+        let mut params = vec![];
+        let mut checks = vec![];
+        for var in self.variables.iter() {
+            let Some(typ) = var.typ.as_ref().filter(|typ| typ.has_limits()) else {
+                continue;
+            };
+            let name = &var.name;
+            let Some(check) = typ.gen_check(&quote!(value), &name.to_lit_str(), error_name) else {
+                continue;
+            };
+            let var_type = var.gen_type();
+            names.push(name);
+            params.push(quote!(#name: &#var_type));
+            checks.push(if var.optional.is_some() {
+                quote!(if let Some(value) = #name { #check })
+            } else {
+                quote!({ let value = #name; #check })
+            });
+        }
+        let request = &self.request;
+        let struct_args = [
+            request.data.as_ref().and_then(|data| {
+                Some((
+                    &data.data,
+                    data.data_var.as_ref()?,
+                    data.optional.is_some(),
+                    data.array.is_some(),
+                ))
+            }),
+            request
+                .query
+                .as_ref()
+                .zip(request.query_var.as_ref())
+                .map(|(config, var)| (config, var, false, false)),
+            request
+                .header
+                .as_ref()
+                .zip(request.header_var.as_ref())
+                .map(|(config, var)| (config, var, false, false)),
+        ];
+        for (config, var, optional, array) in struct_args.into_iter().flatten() {
+            if config.fields.iter().any(|field| field.has_limits()) {
+                let struct_name = &config.struct_name;
+                let (data_type, check) = if array {
+                    (
+                        quote!(Vec<#struct_name>),
+                        quote!(for value in value {
+                            value.validate()?;
+                        }),
+                    )
+                } else {
+                    (quote!(#struct_name), quote!(value.validate()?;))
+                };
+                names.push(var);
+                if optional {
+                    params.push(quote!(#var: &Option<#data_type>));
+                    checks.push(quote!(if let Some(value) = #var { #check }));
+                } else {
+                    params.push(quote!(#var: &#data_type));
+                    checks.push(quote!({ let value = #var; #check }));
+                }
+            }
+        }
+        if checks.is_empty() {
+            return None;
+        }
+        let client_error_name = client.error_name();
+        let into = client.error_into();
+        Some(quote! {
+            fn check_args(#(#params),*) -> Result<(), #error_name> {
+                #(#checks)*
+                Ok(())
+            }
+            if let Err(err) = check_args(#(&#names),*) {
+                return Err(#client_error_name::from(err)#into);
+            }
+        })
+    }
+
+    // the success type of the method, and the statements turning `response`
+    // into it
+    fn gen_output(response: Option<&ApiResponse>, client: &Client) -> (TokenStream, TokenStream) {
+        let Some(response) = response else {
+            return (quote!(()), quote!(Ok(())));
+        };
+        // the body is read first, an optional response is `None` when it is
+        // empty or blank, whatever the status and framing
+        let (read_text, read_bytes) = if client.manual_decompress {
+            let error_name = client.error_name();
+            let into = client.error_into();
+            let read_bytes = quote! {
+                let headers = response.headers().clone();
+                let body = match Self::decompress(&headers, response.bytes().await?) {
+                    Ok(body) => body,
+                    Err(err) => {
+                        return Err(#error_name::Decode {
+                            source: Box::new(err),
+                            body: None,
+                        }#into);
+                    }
+                };
+            };
+            (
+                quote! {
+                    #read_bytes
+                    let body = String::from_utf8_lossy(&body).into_owned();
+                },
+                read_bytes,
+            )
+        } else {
+            (
+                quote!(let body = response.text().await?;),
+                quote!(let body = response.bytes().await?;),
+            )
+        };
+        let (mut output, read, blank, mut value) = if response.text.is_some() {
+            (
+                quote!(String),
+                read_text.clone(),
+                quote!(body.trim().is_empty()),
+                quote!(body),
+            )
+        } else if let Some(data) = &response.data {
+            let struct_name = &data.data.struct_name;
+            let error_name = client.error_name();
+            let into = client.error_into();
+            // the body is kept for the decode error with `capture_body_on_error`
+            let (read, blank, parse, body) = match (&data.data_type, client.capture_body_on_error) {
+                (DataType::Form(_), true) => (
+                    read_text.clone(),
+                    quote!(body.trim().is_empty()),
+                    quote!(#struct_name::from_form(&body)),
+                    quote!(Some(body)),
+                ),
+                (DataType::Form(_), false) => (
+                    read_text.clone(),
+                    quote!(body.trim().is_empty()),
+                    quote!(#struct_name::from_form(&body)),
+                    quote!(None),
+                ),
+                (_, true) => (
+                    read_text.clone(),
+                    quote!(body.trim().is_empty()),
+                    quote!(::power_reqwest::__private::serde_json::from_str::<#struct_name>(&body)),
+                    quote!(Some(body)),
+                ),
+                (_, false) => (
+                    read_bytes.clone(),
+                    quote!(body.iter().all(u8::is_ascii_whitespace)),
+                    quote!(::power_reqwest::__private::serde_json::from_slice::<#struct_name>(&body)),
+                    quote!(None),
+                ),
+            };
+            let decode = quote! {
+                match #parse {
+                    Ok(data) => data,
+                    Err(err) => {
+                        return Err(#error_name::Decode {
+                            source: Box::new(err),
+                            body: #body,
+                        }#into);
+                    }
+                }
+            };
+            if client.response_arc {
+                (
+                    quote!(std::sync::Arc<#struct_name>),
+                    read,
+                    blank,
+                    quote!(std::sync::Arc::new(#decode)),
+                )
+            } else {
+                (quote!(#struct_name), read, blank, decode)
+            }
+        } else {
+            return (quote!(()), quote!(Ok(())));
+        };
+        if response.optional.is_some() {
+            output = quote!(Option<#output>);
+            value = quote! {
+                if #blank {
+                    None
+                } else {
+                    Some(#value)
+                }
+            };
+        }
+        value = quote!({
+            #read
+            #value
+        });
+        if response.rate_limit_info.is_some() {
+            let rate_limit_info_name = client.rate_limit_info_name();
+            output = quote!((#output, Option<#rate_limit_info_name>));
+            value = quote! {{
+                let rate_limit_info = #rate_limit_info_name::from_headers(response.headers());
+                (#value, rate_limit_info)
+            }};
+        }
+        (output, quote!(Ok(#value)))
+    }
+}
+
+impl Variable {
+    fn gen_arg(&self) -> TokenStream {
+        let name = &self.name;
+        let typ = self.gen_type();
+        quote!(#name: #typ)
+    }
+
+    fn is_required_string(&self) -> bool {
+        self.optional.is_none() && matches!(self.typ, None | Some(Type::String(_)))
+    }
+
+    fn gen_type(&self) -> TokenStream {
+        let typ = if let Some(typ) = &self.typ {
+            typ.to_type().to_token_stream()
+        } else {
+            quote!(String)
+        };
+        if self.optional.is_some() {
+            quote!(Option<#typ>)
+        } else {
+            typ
+        }
+    }
+}
+
+impl SuccessStatus {
+    fn gen_success_fn(&self, api_name: &Ident) -> TokenStream {
+        let fn_name = api_name.with_suffix("_is_success");
+        let codes = &self.codes;
+        quote! {
+            pub fn #fn_name(status: ::power_reqwest::__private::reqwest::StatusCode) -> bool {
+                matches!(status.as_u16(), #(#codes)|*)
+            }
+        }
+    }
+}
+
+impl RequestKey {
+    fn gen_key_fn(&self, api_name: &Ident, variables: &[Variable]) -> TokenStream {
+        let fn_name = api_name.with_suffix("_key");
+        let args = self.names.iter().map(|name| {
+            let typ = variables
+                .iter()
+                .find(|var| var.name.eq(name))
+                .expect("key names are checked against the variables when parsing")
+                .gen_type();
+            quote!(#name: &#typ)
+        });
+        let names = &self.names;
+        let prefix = api_name.to_string();
+        quote! {
+            pub fn #fn_name(&self, #(#args),*) -> String {
+                // the key fields as a json array, so values cannot run into
+                // each other
+                let values = ::power_reqwest::__private::serde_json::to_string(&(#(#names,)*)).unwrap_or_default();
+                format!("{}:{}", #prefix, values)
+            }
+        }
+    }
+}
+
+impl Variable {
+    fn gen_url_arg(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        let name = &self.name;
+        if self.client_option {
+            let opt = options.get(name).unwrap();
+            if opt.optional.is_some() {
+                if let Some(default) = &opt.default {
+                    quote!(self.options.#name.as_ref().map(|v|v.to_owned()).unwrap_or(#default))
+                } else {
+                    quote!(self.options.#name.as_ref().map(|v|v.to_owned()).unwrap_or_default())
+                }
+            } else {
+                quote!(&self.options.#name)
+            }
+        } else {
+            quote!(&#name)
+        }
+    }
+}
+
+impl ApiUri {
+    // `let mut req = ...;` for the url, path segments and query pairs of the
+    // literal are encoded by `reqwest::Url`
+    fn gen_new_request(
+        &self,
+        method: &Ident,
+        options: &HashMap<Ident, Field>,
+        based: bool,
+    ) -> TokenStream {
+        let Self {
+            uri_format,
+            uri_variables,
+            schema,
+            uri_path,
+            uri_query,
+            fragment,
+            ..
+        } = self;
+        if schema.is_none() {
+            let url = Self::gen_format_expr(uri_format, uri_variables, options);
+            if based {
+                return quote! {
+                    let path = #url;
+                    let mut req = self.inner.request(
+                        ::power_reqwest::__private::reqwest::Method::#method,
+                        format!("{}{}", Self::BASE_URLS[0], path),
+                    );
+                };
+            }
+            return quote!(let mut req = self.inner.request(::power_reqwest::__private::reqwest::Method::#method, #url););
+        }
+
+        // scheme, host and port
+        let format = uri_format.value();
+        let authority = format.find("://").map(|i| i + 3).unwrap_or_default();
+        let path_start = format[authority..]
+            .find('/')
+            .map(|i| authority + i)
+            .unwrap_or(format.len());
+        let base_format = (&format[..path_start], uri_format.span()).to_lit_str();
+        let base_vars = uri_variables
+            .iter()
+            .take(base_format.value().matches("{}").count())
+            .cloned()
+            .collect::<Vec<_>>();
+        let base = Self::gen_format_expr(&base_format, &base_vars, options);
+
+        let segments = uri_path.as_ref().map(
+            |ApiUriPath {
+                 segments,
+                 last_slash,
+             }| {
+                let segments = segments.iter().map(|seg| match seg {
+                    ApiUriSeg::Static(s) => quote!(segments.push(#s);),
+                    ApiUriSeg::Var(var) => {
+                        let value = var.gen_url_arg(options);
+                        quote!(segments.push(&(#value).to_string());)
+                    }
+                });
+                let last_slash = last_slash.then(|| quote!(segments.push("");));
+                quote! {
+                    if let Ok(mut segments) = url.path_segments_mut() {
+                        segments.pop_if_empty();
+                        #(#segments)*
+                        #last_slash
+                    }
+                }
+            },
+        );
+
+        // params with values are sent with the `query` struct
+        let query = uri_query.as_ref().map(|ApiUriQuery { fields }| {
+            let pairs = fields
+                .iter()
+                .map(|Field { name, .. }| quote!(query.append_key_only(#name);));
+            quote! {
+                {
+                    let mut query = url.query_pairs_mut();
+                    #(#pairs)*
+                }
+            }
+        });
+
+        let fragment = fragment
+            .as_ref()
+            .map(|fragment| quote!(url.set_fragment(Some(#fragment));));
+
+        let url = if segments.is_some() || query.is_some() || fragment.is_some() {
+            quote!(mut url)
+        } else {
+            quote!(url)
+        };
+        quote! {
+            let base = #base;
+            let mut req = match ::power_reqwest::__private::reqwest::Url::parse(&base) {
+                Ok(#url) => {
+                    #segments
+                    #query
+                    #fragment
+                    self.inner.request(::power_reqwest::__private::reqwest::Method::#method, url)
+                }
+                // let reqwest keep the parse error until sending
+                Err(_) => self.inner.request(::power_reqwest::__private::reqwest::Method::#method, base),
+            };
+        }
+    }
+
+    fn gen_format_expr(
+        uri_format: &LitStr,
+        uri_variables: &[Variable],
+        options: &HashMap<Ident, Field>,
+    ) -> TokenStream {
+        if uri_variables.is_empty() {
+            quote!(#uri_format.to_owned())
+        } else {
+            let args = uri_variables.iter().map(|var| var.gen_url_arg(options));
+            quote!(format!(#uri_format, #(#args),*))
+        }
+    }
+    fn gen_basic_auth(&self) -> Option<TokenStream> {
+        let Self { user, passwd, .. } = self;
+        if let Some(user) = user {
+            let pass = if let Some(passwd) = passwd {
+                quote!(Some(#passwd))
+            } else {
+                quote!(None::<&str>)
+            };
+            Some(quote!(req = req.basic_auth(#user, #pass);))
+        } else {
+            None
+        }
+    }
+}
+
+impl BasicAuth {
+    fn gen_basic_auth(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        let user = self.user.gen_value(options);
+        let pass = if let Some(passwd) = &self.passwd {
+            let passwd = passwd.gen_value(options);
+            quote!(Some(#passwd))
+        } else {
+            quote!(None::<&str>)
+        };
+        quote!(req = req.basic_auth(#user, #pass);)
+    }
+}
+
+impl ApiResponseData {
+    fn gen_decoder(&self) -> Option<TokenStream> {
+        let struct_name = &self.data.struct_name;
+        match self.data_type {
+            DataType::Form(_) => Some(quote! {
+                impl #struct_name {
+                    pub fn from_form(text: &str) -> Result<Self, ::power_reqwest::__private::serde_urlencoded::de::Error> {
+                        ::power_reqwest::__private::serde_urlencoded::from_str(text)
+                    }
+                }
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl PageInfo {
+    fn gen_accessor(&self, data: &ApiResponseData, page_info_name: &Ident) -> TokenStream {
+        let struct_name = &data.data.struct_name;
+        let [total, page, size] = [&self.total, &self.page, &self.size].map(|name| {
+            let optional = data
+                .data
+                .fields
+                .iter()
+                .any(|field| field.field_name.eq(name) && field.optional.is_some());
+            // negative or missing values count as zero
+            if optional {
+                quote!(self.#name.and_then(|v| u64::try_from(v).ok()).unwrap_or_default())
+            } else {
+                quote!(u64::try_from(self.#name).unwrap_or_default())
+            }
+        });
+        quote! {
+            impl #struct_name {
+                pub fn page_info(&self) -> #page_info_name {
+                    #page_info_name {
+                        total: #total,
+                        page: #page,
+                        size: #size,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ApiRequest {
+    // serializes every request struct built from its examples, or defaults
+    fn gen_smoke_test(&self, api_name: &Ident) -> Option<TokenStream> {
+        let build = |config: &BracedConfig| {
+            let struct_name = &config.struct_name;
+            if config.fields.iter().any(|field| field.example.is_some()) {
+                quote!(#struct_name::example())
+            } else {
+                quote!(<#struct_name as Default>::default())
+            }
+        };
+        let mut checks = vec![];
+        if let Some(query) = &self.query {
+            let value = build(query);
+            let nested = query.fields.iter().any(|field| {
+                matches!(
+                    &field.typ,
+                    Some(Type::Object(_)) | Some(Type::Map(_)) | Some(Type::List(_))
+                )
+            });
+            checks.push(if nested {
+                quote!(::power_reqwest::__private::serde_json::to_value(&#value).expect("query should serialize");)
+            } else {
+                quote!(::power_reqwest::__private::serde_urlencoded::to_string(&#value).expect("query should serialize");)
+            });
+        }
+        if let Some(data) = &self.data {
+            let configs = if let Some(data_match) = &data.data_match {
+                data_match.arms.iter().map(|arm| &arm.data).collect()
+            } else {
+                vec![&data.data]
+            };
+            for config in configs {
+                let value = build(config);
+                match data.data_type {
+                    DataType::Json(_) => checks.push(
+                        quote!(::power_reqwest::__private::serde_json::to_string(&#value).expect("request data should serialize");),
+                    ),
+                    DataType::Urlencoded(_) | DataType::Form(_) => checks.push(
+                        quote!(::power_reqwest::__private::serde_urlencoded::to_string(&#value).expect("request data should serialize");),
+                    ),
+                    DataType::Multipart(_) => {}
+                }
+            }
+        }
+        if checks.is_empty() {
+            return None;
+        }
+        let test_name = api_name
+            .to_ident_with_case(Case::Snake)
+            .with_suffix("_request_serializes");
+        Some(quote! {
+            #[cfg(test)]
+            #[test]
+            fn #test_name() {
+                #(#checks)*
+            }
+        })
+    }
+
+    fn gen_header(&self, options: &HashMap<Ident, Field>) -> Option<TokenStream> {
+        let header = self.header.as_ref()?;
+        let init = header.gen_init(&self.header_var, options);
+        let headers = header.fields.iter().map(|field| {
+            let Field {
+                name, field_name, ..
+            } = field;
+            let string = matches!(
+                field.typ,
+                None | Some(Type::String(_)) | Some(Type::JsonText(_))
+            );
+            if field.optional.is_some() {
+                let value = if string {
+                    quote!(value)
+                } else {
+                    quote!(value.to_string())
+                };
+                quote! {
+                    if let Some(value) = headers.#field_name {
+                        req = req.header(#name, #value);
+                    }
+                }
+            } else if string {
+                quote!(req = req.header(#name, headers.#field_name);)
+            } else {
+                quote!(req = req.header(#name, headers.#field_name.to_string());)
+            }
+        });
+        Some(quote! {
+            let headers = #init;
+            #(#headers)*
+        })
+    }
+
+    fn gen_query(&self, options: &HashMap<Ident, Field>) -> Option<TokenStream> {
+        let query = self.query.as_ref()?;
+        let init = query.gen_init(&self.query_var, options);
+        let nested = query.fields.iter().any(|field| match &field.typ {
+            Some(Type::Enum(e)) => !e.variant_fields.is_empty(),
+            typ => matches!(
+                typ,
+                Some(Type::Object(_)) | Some(Type::Map(_)) | Some(Type::List(_))
+            ),
+        });
+        let query = if nested {
+            // serde_urlencoded cannot encode nested values or sequences, even
+            // of plain strings and enums, flatten them into bracketed keys
+            let pairs = gen_flatten_pairs(&query.fields, KeyStyle::Bracket);
+            quote! {
+                match ::power_reqwest::__private::serde_json::to_value(&query) {
+                    Ok(::power_reqwest::__private::serde_json::Value::Object(mut map)) => {
+                        let pairs = #pairs;
+                        req.query(&pairs)
+                    }
+                    // let reqwest keep the serialization error until sending
+                    _ => req.query(&query),
+                }
+            }
+        } else {
+            quote!(req.query(&query))
+        };
+        Some(quote! {
+            let query = #init;
+            req = #query;
+        })
+    }
+}
+
+impl ApiRequestData {
+    fn gen_body(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        let body = self.gen_body_call(&self.data);
+        if let Some(DataMatch {
+            var,
+            enum_name,
+            arms,
+            ..
+        }) = &self.data_match
+        {
+            let arms = arms.iter().map(
+                |DataArm {
+                     name,
+                     data,
+                     variables,
+                 }| {
+                    let body = self.gen_body_call(data);
+                    let init = data.gen_init(&None, options);
+                    let pattern = if variables.is_empty() {
+                        quote!(#enum_name::#name)
+                    } else {
+                        let bindings = variables.iter().map(|var| &var.name);
+                        quote!(#enum_name::#name { #(#bindings),* })
+                    };
+                    quote! {
+                        #pattern => {
+                            let data = #init;
+                            req = #body;
+                        }
+                    }
+                },
+            );
+            quote! {
+                match #var {
+                    #(#arms)*
+                }
+            }
+        } else if let (Some(data_var), Some(_)) = (&self.data_var, self.optional) {
+            let init = self.gen_init(options);
+            quote! {
+                if let Some(#data_var) = #data_var {
+                    let data = #init;
+                    req = #body;
+                }
+            }
+        } else {
+            let init = self.gen_init(options);
+            quote! {
+                let data = #init;
+                req = #body;
+            }
+        }
+    }
+
+    // an array body fills the fixed values of every element
+    fn gen_init(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        match (&self.data_var, &self.array) {
+            (Some(data_var), Some(bracket)) => {
+                let item = Some(("item", bracket.span.join()).to_ident());
+                let init = self.data.gen_init(&item, options);
+                quote!(#data_var.into_iter().map(|item| #init).collect::<Vec<_>>())
+            }
+            _ => self.data.gen_init(&self.data_var, options),
+        }
+    }
+
+    // text parts come from the serialized data, so renames and formatters
+    // apply; files are taken from the struct as they are
+    fn gen_multipart_form(&self) -> TokenStream {
+        let files = self.data.fields.iter().filter(|field| match &field.typ {
+            Some(Type::File(_)) => true,
+            Some(Type::List(ListType { element_type, .. })) => {
+                matches!(element_type.as_ref(), Type::File(_))
+            }
+            _ => false,
+        });
+        let file_names = files.clone().map(|field| &field.name);
+        let file_parts = files.map(|field| {
+            let Field {
+                name,
+                field_name,
+                optional,
+                ..
+            } = field;
+            let mime = match &field.typ {
+                Some(Type::File(file)) => file.mime.as_ref(),
+                Some(Type::List(ListType { element_type, .. })) => match element_type.as_ref() {
+                    Type::File(file) => file.mime.as_ref(),
+                    _ => None,
+                },
+                _ => None,
+            }
+            .map(|mime| quote!(.mime_str(#mime).expect("mime type is checked by the macro")));
+            let part = quote! {
+                let (file_name, bytes) = value;
+                form = form.part(
+                    #name,
+                    ::power_reqwest::__private::reqwest::multipart::Part::bytes(bytes)
+                        .file_name(file_name)
+                        #mime,
+                );
+            };
+            match (&field.typ, optional) {
+                (Some(Type::List(_)), Some(_)) => {
+                    quote!(for value in data.#field_name.into_iter().flatten() { #part })
+                }
+                (Some(Type::List(_)), None) => quote!(for value in data.#field_name { #part }),
+                (_, Some(_)) => quote!(if let Some(value) = data.#field_name { #part }),
+                (_, None) => quote!(let value = data.#field_name; #part),
+            }
+        });
+        quote! {{
+            let mut form = ::power_reqwest::__private::reqwest::multipart::Form::new();
+            // a body failing to serialize is sent with its files only
+            if let Ok(::power_reqwest::__private::serde_json::Value::Object(map)) = ::power_reqwest::__private::serde_json::to_value(&data) {
+                for (name, value) in map {
+                    if [#(#file_names),*].contains(&name.as_str()) {
+                        continue;
+                    }
+                    form = match value {
+                        ::power_reqwest::__private::serde_json::Value::Null => form,
+                        ::power_reqwest::__private::serde_json::Value::String(v) => form.text(name, v),
+                        v => form.text(name, v.to_string()),
+                    };
+                }
+            }
+            #(#file_parts)*
+            req.multipart(form)
+        }}
+    }
+
+    // nested urlencoded values are flattened after the fields of `data`
+    fn gen_body_call(&self, data: &BracedConfig) -> TokenStream {
+        match self.data_type {
+            DataType::Json(_) => quote!(req.json(&data)),
+            DataType::Multipart(_) => self.gen_multipart_form(),
+            DataType::Urlencoded(_) | DataType::Form(_) => {
+                let body = if let Some(UrlEncoding::Rfc3986(_)) = self.encoding {
+                    // serde_urlencoded writes application/x-www-form-urlencoded,
+                    // patch the differences to RFC 3986 afterwards. like
+                    // `form`, a Content-Type declared in the header is kept
+                    quote! {
+                        match ::power_reqwest::__private::serde_urlencoded::to_string(&data) {
+                            Ok(body) => {
+                                let req = req.body(
+                                    body.replace('+', "%20")
+                                        .replace('*', "%2A")
+                                        .replace("%7E", "~"),
+                                );
+                                match req.try_clone().map(|req| req.build_split()) {
+                                    Some((client, Ok(mut request))) => {
+                                        request
+                                            .headers_mut()
+                                            .entry(::power_reqwest::__private::reqwest::header::CONTENT_TYPE)
+                                            .or_insert(::power_reqwest::__private::reqwest::header::HeaderValue::from_static(
+                                                "application/x-www-form-urlencoded",
+                                            ));
+                                        ::power_reqwest::__private::reqwest::RequestBuilder::from_parts(client, request)
+                                    }
+                                    // a failed build is left to reqwest until sending
+                                    _ => req,
+                                }
+                            }
+                            // let reqwest keep the serialization error until sending
+                            Err(_) => req.form(&data),
+                        }
+                    }
+                } else {
+                    quote!(req.form(&data))
+                };
+                let nested = data.fields.iter().any(|field| {
+                    matches!(
+                        &field.typ,
+                        Some(Type::Object(_)) | Some(Type::Map(_)) | Some(Type::List(_))
+                    )
+                });
+                if !nested {
+                    return body;
+                }
+                // serde_urlencoded takes no nested values or sequences, they
+                // are flattened into dotted keys
+                let pairs = gen_flatten_pairs(&data.fields, KeyStyle::Dotted);
+                quote! {
+                    match ::power_reqwest::__private::serde_json::to_value(&data) {
+                        Ok(::power_reqwest::__private::serde_json::Value::Object(mut map)) => {
+                            let data = #pairs;
+                            #body
+                        }
+                        // let reqwest keep the serialization error until sending
+                        _ => req.form(&data),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Transfer {
+    // the framing of a `bytes` body
+    fn gen_body(&self, var: &Ident) -> TokenStream {
+        match self {
+            Self::Chunked(_) => quote! {
+                req = req.body(::power_reqwest::__private::reqwest::Body::wrap_stream(
+                    ::power_reqwest::__private::futures_util::stream::once(async move { Ok::<_, std::io::Error>(#var) }),
+                ));
+            },
+            Self::Length(_) => quote! {
+                req = req
+                    .header(::power_reqwest::__private::reqwest::header::CONTENT_LENGTH, #var.len())
+                    .body(#var);
+            },
+        }
+    }
+}
+
+impl DataMatch {
+    fn gen_types(&self) -> Vec<TokenStream> {
+        let Self {
+            enum_name, arms, ..
+        } = self;
+        let variants = arms.iter().map(
+            |DataArm {
+                 name, variables, ..
+             }| {
+                if variables.is_empty() {
+                    quote!(#name)
+                } else {
+                    let fields = variables.iter().map(|var| var.gen_arg());
+                    quote!(#name { #(#fields),* })
+                }
+            },
+        );
+        let mut types = vec![quote! {
+            pub enum #enum_name {
+                #(#variants),*
+            }
+        }];
+        for arm in arms.iter() {
+            types.extend(arm.data.gen_obj_structs(false, SerdeDirection::Serialize));
+        }
+        types
+    }
+}
+
+impl BracedConfig {
+    // `Options::builder().ak("..").sk("..")`, starting from the defaults
+    fn gen_builder(&self) -> TokenStream {
+        let struct_name = &self.struct_name;
+        let setters = self.fields.iter().map(|field| {
+            let field_name = &field.field_name;
+            if let Some(Type::List(ListType { element_type, .. })) = &field.typ {
+                return gen_list_setters(field, element_type);
+            }
+            let typ = if let Some(typ) = &field.typ {
+                typ.to_type().to_token_stream()
+            } else {
+                quote!(String)
+            };
+            let (arg_type, value) = if matches!(field.typ, None | Some(Type::String(_))) {
+                (quote!(impl Into<String>), quote!(#field_name.into()))
+            } else {
+                (typ, quote!(#field_name))
+            };
+            let value = if field.optional.is_some() {
+                quote!(Some(#value))
+            } else {
+                value
+            };
+            quote! {
+                pub fn #field_name(mut self, #field_name: #arg_type) -> Self {
+                    self.#field_name = #value;
+                    self
+                }
+            }
+        });
+        quote! {
+            impl #struct_name {
+                pub fn builder() -> Self {
+                    Default::default()
+                }
+                #(#setters)*
+            }
+        }
+    }
+
+    fn gen_init(&self, outer_var: &Option<Ident>, options: &HashMap<Ident, Field>) -> TokenStream {
+        if let Some(outer_var) = outer_var {
+            // fixed values are not left to the caller
+            let assigns = self
+                .fields
+                .iter()
+                .filter(|field| {
+                    field.expr.is_some() || matches!(field.typ, Some(Type::Constant(_)))
+                })
+                .map(|field| {
+                    let field_name = &field.field_name;
+                    let value = field.gen_value(options);
+                    quote!(data.#field_name = #value;)
+                });
+            quote!({
+                let mut data = #outer_var;
+                #(#assigns)*
+                data
+            })
+        } else {
+            make_object_init(&self.struct_name, &self.fields, options)
+        }
+    }
+}
+
+// a block turning the serialized fields in `map` into pairs, in the order
+// the fields are declared. nested objects are walked the same way, map
+// values and whatever is left in `map` follow in serialized order
+fn gen_flatten_pairs(fields: &[Field], style: KeyStyle) -> TokenStream {
+    let (nested_key, items) = match style {
+        KeyStyle::Bracket => (
+            quote!(format!("{key}[{k}]")),
+            quote! {
+                for v in items {
+                    flatten(format!("{key}[]"), v, pairs);
+                }
+            },
+        ),
+        KeyStyle::Dotted => (
+            quote!(format!("{key}.{k}")),
+            quote! {
+                for (i, v) in items.into_iter().enumerate() {
+                    flatten(format!("{key}.{}", i + 1), v, pairs);
+                }
+            },
+        ),
+    };
+    let walk = gen_flatten_fields(fields, style, true);
+    quote! {{
+        fn flatten(key: String, value: ::power_reqwest::__private::serde_json::Value, pairs: &mut Vec<(String, String)>) {
+            match value {
+                ::power_reqwest::__private::serde_json::Value::Null => {}
+                ::power_reqwest::__private::serde_json::Value::Object(map) => {
+                    for (k, v) in map {
+                        flatten(#nested_key, v, pairs);
+                    }
+                }
+                ::power_reqwest::__private::serde_json::Value::Array(items) => {
+                    #items
+                }
+                ::power_reqwest::__private::serde_json::Value::String(v) => pairs.push((key, v)),
+                v => pairs.push((key, v.to_string())),
+            }
+        }
+        let mut pairs = vec![];
+        #walk
+        pairs
+    }}
+}
+
+fn gen_flatten_fields(fields: &[Field], style: KeyStyle, top: bool) -> TokenStream {
+    let walk = fields.iter().map(|field| {
+        let name = &field.name;
+        let key = match (top, style) {
+            (true, _) => quote!(String::from(#name)),
+            (false, KeyStyle::Bracket) => quote!(format!("{key}[{}]", #name)),
+            (false, KeyStyle::Dotted) => quote!(format!("{key}.{}", #name)),
+        };
+        let value = gen_flatten_value(field.typ.as_ref(), style);
+        quote! {
+            if let Some(value) = map.remove(#name) {
+                let key = #key;
+                #value
+            }
+        }
+    });
+    let rest_key = match (top, style) {
+        (true, _) => quote!(k),
+        (false, KeyStyle::Bracket) => quote!(format!("{key}[{k}]")),
+        (false, KeyStyle::Dotted) => quote!(format!("{key}.{k}")),
+    };
+    quote! {
+        #(#walk)*
+        for (k, value) in map {
+            flatten(#rest_key, value, &mut pairs);
+        }
+    }
+}
+
+// only objects with declared fields need walking, anything else is left to
+// `flatten`
+fn gen_flatten_value(typ: Option<&Type>, style: KeyStyle) -> TokenStream {
+    fn has_fields(typ: &Type) -> bool {
+        match typ {
+            Type::Object(obj) => !obj.fields.is_empty(),
+            Type::List(list) => has_fields(&list.element_type),
+            _ => false,
+        }
+    }
+    match typ {
+        Some(Type::Object(obj)) if !obj.fields.is_empty() => {
+            let walk = gen_flatten_fields(&obj.fields, style, false);
+            quote! {
+                match value {
+                    ::power_reqwest::__private::serde_json::Value::Object(mut map) => {
+                        #walk
+                    }
+                    value => flatten(key, value, &mut pairs),
+                }
+            }
+        }
+        Some(Type::List(list)) if has_fields(&list.element_type) => {
+            let item = gen_flatten_value(Some(&list.element_type), style);
+            let items = match style {
+                KeyStyle::Bracket => quote! {
+                    for value in items {
+                        let key = format!("{key}[]");
+                        #item
+                    }
+                },
+                KeyStyle::Dotted => quote! {
+                    for (i, value) in items.into_iter().enumerate() {
+                        let key = format!("{key}.{}", i + 1);
+                        #item
+                    }
+                },
+            };
+            quote! {
+                match value {
+                    ::power_reqwest::__private::serde_json::Value::Array(items) => {
+                        #items
+                    }
+                    value => flatten(key, value, &mut pairs),
+                }
+            }
+        }
+        _ => quote!(flatten(key, value, &mut pairs)),
+    }
+}
+
+// `tags(..)` takes any iterator, `add_tag(..)` pushes one element
+fn gen_list_setters(field: &Field, element_type: &Type) -> TokenStream {
+    let field_name = &field.field_name;
+    let name = field_name.to_string();
+    let singular = if let Some(stem) = name.strip_suffix("ies") {
+        format!("{stem}y")
+    } else if let Some(stem) = name.strip_suffix('s').filter(|_| !name.ends_with("ss")) {
+        stem.to_owned()
+    } else {
+        name
+    };
+    let add_fn = (format!("add_{singular}"), field_name.span()).to_ident();
+    let element = (singular, field_name.span()).to_ident();
+    let typ = element_type.to_type();
+    let (element_type, value) = if matches!(element_type, Type::String(_)) {
+        (quote!(impl Into<String>), quote!(#element.into()))
+    } else {
+        (quote!(#typ), quote!(#element))
+    };
+    let (list, collected) = if field.optional.is_some() {
+        (
+            quote!(self.#field_name.get_or_insert_with(Vec::new)),
+            quote!(Some(#field_name.into_iter().collect())),
+        )
+    } else {
+        (
+            quote!(self.#field_name),
+            quote!(#field_name.into_iter().collect()),
+        )
+    };
+    quote! {
+        pub fn #field_name(mut self, #field_name: impl IntoIterator<Item = #typ>) -> Self {
+            self.#field_name = #collected;
+            self
+        }
+        pub fn #add_fn(mut self, #element: #element_type) -> Self {
+            #list.push(#value);
+            self
+        }
+    }
+}
+
+fn make_object_init(
+    name: &Ident,
+    fields: &[Field],
+    options: &HashMap<Ident, Field>,
+) -> TokenStream {
+    let field_inits = fields.iter().map(|field| {
+        let field_name = &field.field_name;
+        let value = field.gen_value(options);
+        quote!(#field_name: #value)
+    });
+    quote! {
+        #name {
+            #(#field_inits),*
+        }
+    }
+}
+
+impl Field {
+    fn gen_value(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        let mut value = self.gen_unconditional_value(options);
+        if let Some(lit) = self.cow_literal() {
+            value = if self.optional.is_some() {
+                quote!(Some(std::borrow::Cow::Borrowed(#lit)))
+            } else {
+                quote!(std::borrow::Cow::Borrowed(#lit))
+            };
+        } else if self.cow {
+            value = if self.optional.is_some() {
+                quote!(#value.map(std::borrow::Cow::Owned))
+            } else {
+                quote!(std::borrow::Cow::Owned(#value))
+            };
+        }
+        if self.stringify {
+            value = if self.optional.is_some() {
+                quote!(#value.map(|v| v.to_string()))
+            } else {
+                quote!(#value.to_string())
+            };
+        }
+        if let Some(condition) = &self.condition {
+            let condition = condition.gen_value(options);
+            quote!(if #condition { #value } else { None })
+        } else {
+            value
+        }
+    }
+
+    // string literals are borrowed rather than allocated
+    fn cow_literal(&self) -> Option<&LitStr> {
+        match (&self.expr, &self.typ) {
+            (Some(Expr::Constant(Constant::String(lit))), _)
+            | (None, Some(Type::Constant(Constant::String(lit))))
+                if self.cow =>
+            {
+                Some(lit)
+            }
+            _ => None,
+        }
+    }
+
+    fn gen_unconditional_value(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        let Self {
+            optional,
+            typ,
+            expr,
+            default,
+            ..
+        } = self;
+        let value = if let Some(expr) = expr {
+            match expr {
+                // already an `Option`
+                Expr::Variable(Variable {
+                    optional: Some(_), ..
+                }) => return expr.gen_value(options),
+                Expr::Or(OrExpr {
+                    variable:
+                        variable @ Variable {
+                            optional: Some(_), ..
+                        },
+                    default,
+                    ..
+                }) => {
+                    let var = variable.gen_value(options);
+                    quote!(#var.unwrap_or_else(|| #default))
+                }
+                Expr::Constant(c) => match typ {
+                    Some(typ) => typ.gen_constant(c),
+                    None => expr.gen_value(options),
+                },
+                _ => expr.gen_value(options),
             }
-        },
-    );
-
-    let field_inits = fields.iter().map(
-        |Field {
-             field_name,
-             default,
-             optional,
-             ..
-         }| {
-            let mut default = default
-                .as_ref()
-                .map(|x| x.to_token_stream())
-                .unwrap_or(quote!(Default::default()));
+        } else if let Some(Type::Object(obj)) = typ {
+            make_object_init(&obj.struct_name, &obj.fields, options)
+        } else if let Some(default) = default {
+            default.to_token_stream()
+        } else if optional.is_some() {
+            return quote!(None);
+        } else {
+            quote!(Default::default())
+        };
+        if optional.is_some() {
+            quote!(Some(#value))
+        } else {
+            value
+        }
+    }
+}
+
+impl Expr {
+    fn gen_value(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        match self {
+            Self::Constant(c) => c.to_token_stream(),
+            Self::Variable(var) => var.gen_value(options),
+            Self::Json(JsonStringifyFn { variable, .. }) => {
+                let var = variable.gen_value(options);
+                quote!(::power_reqwest::__private::serde_json::to_string(&#var).unwrap_or_default())
+            }
+            Self::Format(FormatFn {
+                format_text, args, ..
+            }) => {
+                let args = args.iter().flatten().map(|arg| arg.gen_value(options));
+                quote!(format!(#format_text, #(#args),*))
+            }
+            Self::Datetime(DatetimeFn {
+                variable, format, ..
+            }) => {
+                let var = variable.gen_value(options);
+                quote!(#var.format(#format).to_string())
+            }
+            Self::Timestamp(UnixTimestampUintFn { variable, .. }) => {
+                let var = variable.gen_value(options);
+                quote!(#var.timestamp() as u64)
+            }
+            Self::Join(JoinStringFn { variable, sep, .. }) => {
+                let var = variable.gen_value(options);
+                quote!(#var.join(#sep))
+            }
+            Self::Transform(StringTransformFn {
+                variable,
+                transform,
+                ..
+            }) => {
+                let var = variable.gen_value(options);
+                match transform {
+                    StringTransform::Upper => quote!(#var.to_uppercase()),
+                    StringTransform::Lower => quote!(#var.to_lowercase()),
+                    StringTransform::Trim => quote!(#var.trim().to_owned()),
+                }
+            }
+            Self::Or(OrExpr { variable, .. }) => variable.gen_value(options),
+            Self::Default(_) => quote!(Default::default()),
+        }
+    }
+}
+
+impl Variable {
+    fn gen_value(&self, options: &HashMap<Ident, Field>) -> TokenStream {
+        let Self {
+            name,
+            client_option,
+            ..
+        } = self;
+        if *client_option {
+            let opt = options.get(name).unwrap();
+            if opt.optional.is_some() {
+                if let Some(default) = &opt.default {
+                    quote!(self.options.#name.as_ref().map(|v|v.to_owned()).unwrap_or(#default))
+                } else {
+                    quote!(self.options.#name.as_ref().map(|v|v.to_owned()).unwrap_or_default())
+                }
+            } else {
+                quote!(self.options.#name.clone())
+            }
+        } else if self.shared {
+            quote!(#name.clone())
+        } else {
+            quote!(#name)
+        }
+    }
+}
+
+fn make_object_struct(
+    name: &Ident,
+    attrs: &[Attribute],
+    fields: &[Field],
+    serde_defaults: bool,
+    direction: SerdeDirection,
+) -> TokenStream {
+    let default_fn_name = |field_name: &Ident| {
+        (
+            format!(
+                "{}_{}_default",
+                name.to_string().to_case(Case::Snake),
+                field_name
+            ),
+            field_name.span(),
+        )
+            .to_ident()
+    };
+
+    let int_or_str_mod = (
+        format!("{}_int_or_str", name.to_string().to_case(Case::Snake)),
+        name.span(),
+    )
+        .to_ident();
+    let int_or_str = direction.deserializes() && fields.iter().any(|f| f.int_or_str.is_some());
+
+    let fields_in_struct = fields.iter().map(|field| {
+        let Field {
+            name,
+            input_keys,
+            field_name,
+            optional,
+            typ,
+            readonly,
+            writeonly,
+            expr,
+            condition,
+            attrs,
+            ..
+        } = field;
+        let field_type = field.to_field_type();
+
+        let mut serde_options = vec![];
+        if !name.value().eq(&field_name.to_string()) {
+            serde_options.push(quote! {rename = #name});
+        }
+        for key in input_keys {
+            serde_options.push(quote! {alias = #key});
+        }
+
+        if let Some(Type::Datetime(DateTimeType {
+            format: Some(DateTimeFormat { mod_name, .. }),
+            ..
+        })) = typ
+        {
+            let formatter = mod_name.to_lit_str();
+            serde_options.push(quote! {with = #formatter});
+        };
+        if readonly.is_some() {
+            serde_options.push(quote! {skip_serializing});
+        } else if (direction == SerdeDirection::Serialize || condition.is_some())
+            && optional.is_some()
+        {
+            // unset optional request fields are left out rather than sent as
+            // `null`, which many apis reject
+            serde_options.push(quote! {skip_serializing_if = "Option::is_none"});
+        } else if field.skip_empty {
+            serde_options.push(quote! {skip_serializing_if = "Vec::is_empty"});
+        }
+        if writeonly.is_some() {
+            serde_options.push(quote! {skip_deserializing});
+        }
+        if serde_defaults && expr.is_some() {
+            let default_fn = default_fn_name(field_name).to_lit_str();
+            serde_options.push(quote! {default = #default_fn});
+        }
+        if int_or_str && field.int_or_str.is_some() {
             if optional.is_some() {
-                default = quote!(Some(#default));
+                let with = format!("{int_or_str_mod}::deserialize_option");
+                serde_options.push(quote! {deserialize_with = #with});
+                // a missing field is no longer `None` by itself
+                if !(serde_defaults && expr.is_some()) {
+                    serde_options.push(quote! {default});
+                }
+            } else {
+                let with = format!("{int_or_str_mod}::deserialize");
+                serde_options.push(quote! {deserialize_with = #with});
             }
+        }
+        let serde = if !serde_options.is_empty() {
+            Some(quote! {#[serde(#(#serde_options),*)]})
+        } else {
+            None
+        };
+
+        let example = field.example.as_ref().map(|example| {
+            let doc = format!(" Example: {}", example.to_doc());
+            quote!(#[doc = #doc])
+        });
+
+        (
             quote! {
-                #field_name: #default
-            }
-        },
-    );
+                #example
+                #serde
+                #(#attrs)*
+                pub #field_name: #field_type
+            },
+            quote! {
+                #example
+                #(#attrs)*
+                pub #field_name: #field_type
+            },
+        )
+    });
+    // without derives, `#[serde]` attributes are only valid on the shadow
+    // struct of a hand-written `Deserialize`
+    let (fields_in_struct, bare_fields): (Vec<_>, Vec<_>) = fields_in_struct.unzip();
+
+    let field_inits = fields.iter().map(|field| {
+        let Field {
+            field_name,
+            default,
+            optional,
+            cow,
+            ..
+        } = field;
+        let mut default = match (&field.typ, &field.expr) {
+            (Some(typ), Some(Expr::Constant(c))) => typ.gen_constant(c),
+            _ => default
+                .as_ref()
+                .map(|x| x.to_token_stream())
+                .unwrap_or(quote!(Default::default())),
+        };
+        if let Some(lit) = field.cow_literal() {
+            default = quote!(std::borrow::Cow::Borrowed(#lit));
+        } else if *cow {
+            default = quote!(std::borrow::Cow::Owned(#default));
+        }
+        if optional.is_some() {
+            default = quote!(Some(#default));
+        }
+        quote! {
+            #field_name: #default
+        }
+    });
 
     let serde_formatters = fields.iter().filter_map(|Field { typ, .. }| {
         if let Some(Type::Datetime(DateTimeType {
@@ -325,11 +2665,218 @@ fn make_object_struct(name: &Ident, fields: &Vec<Field>) -> TokenStream {
         }
     });
 
+    // `1` and `"1"` both read through `FromStr`
+    let int_or_str_fns = int_or_str.then(|| {
+        quote! {
+            #[allow(dead_code)]
+            mod #int_or_str_mod {
+                use ::power_reqwest::__private::serde::{de::Error, Deserialize, Deserializer};
+
+                pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+                where
+                    D: Deserializer<'de>,
+                    T: std::str::FromStr,
+                    T::Err: std::fmt::Display,
+                {
+                    match ::power_reqwest::__private::serde_json::Value::deserialize(deserializer)? {
+                        ::power_reqwest::__private::serde_json::Value::Number(n) => n.to_string().parse().map_err(D::Error::custom),
+                        ::power_reqwest::__private::serde_json::Value::String(s) => s.trim().parse().map_err(D::Error::custom),
+                        other => Err(D::Error::custom(format!(
+                            "expect an integer or a string, found {other}"
+                        ))),
+                    }
+                }
+
+                pub fn deserialize_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+                where
+                    D: Deserializer<'de>,
+                    T: std::str::FromStr,
+                    T::Err: std::fmt::Display,
+                {
+                    match ::power_reqwest::__private::serde_json::Value::deserialize(deserializer)? {
+                        ::power_reqwest::__private::serde_json::Value::Null => Ok(None),
+                        value => deserialize(value).map(Some).map_err(D::Error::custom),
+                    }
+                }
+            }
+        }
+    });
+
+    let default_fns = fields
+        .iter()
+        .filter(|field| serde_defaults && field.expr.is_some())
+        .map(|field| {
+            let fn_name = default_fn_name(&field.field_name);
+            let field_type = field.to_field_type();
+            let value = field.gen_value(&HashMap::new());
+            quote! {
+                fn #fn_name() -> #field_type {
+                    #value
+                }
+            }
+        });
+
+    let example_fn = if fields.iter().any(|field| field.example.is_some()) {
+        let examples = fields.iter().filter_map(|field| {
+            field.example.as_ref().map(|example| {
+                let field_name = &field.field_name;
+                let mut value = example.to_value().to_token_stream();
+                if field.cow {
+                    value = quote!(std::borrow::Cow::Owned(#value));
+                }
+                if field.optional.is_some() {
+                    value = quote!(Some(#value));
+                }
+                quote!(#field_name: #value,)
+            })
+        });
+        Some(quote! {
+            impl #name {
+                pub fn example() -> Self {
+                    Self {
+                        #(#examples)*
+                        ..Default::default()
+                    }
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    let serde_crate = serde_crate();
+    let serialize = direction
+        .serializes()
+        .then(|| quote!(::power_reqwest::__private::serde::Serialize));
+    let (derive, deserialize) = if !direction.deserializes() {
+        (quote!(#[derive(#serialize)] #serde_crate), None)
+    } else if fields
+        .iter()
+        .any(|field| field.json_path.is_some() || field.require.is_some())
+    {
+        // formatted datetimes go through their serde formatter
+        let decode =
+            |field: &Field| match &field.typ {
+                Some(Type::Datetime(DateTimeType {
+                    format: Some(DateTimeFormat { mod_name, .. }),
+                    ..
+                })) => {
+                    let found = quote!(#mod_name::deserialize(v).map_err(D::Error::custom)?);
+                    if field.optional.is_some() {
+                        quote!(Some(#found))
+                    } else {
+                        found
+                    }
+                }
+                _ if field.int_or_str.is_some() => {
+                    if field.optional.is_some() {
+                        quote!(#int_or_str_mod::deserialize_option(v).map_err(D::Error::custom)?)
+                    } else {
+                        quote!(#int_or_str_mod::deserialize(v).map_err(D::Error::custom)?)
+                    }
+                }
+                _ => quote!(::power_reqwest::__private::serde_json::from_value(v)
+                    .map_err(D::Error::custom)?),
+            };
+        let plain_fields = fields
+            .iter()
+            .zip(fields_in_struct.iter())
+            .filter(|(field, _)| field.json_path.is_none())
+            .map(|(field, tokens)| {
+                if field.require.is_some() {
+                    // kept raw, a missing or null value is reported below
+                    let Field {
+                        name,
+                        input_keys,
+                        field_name,
+                        ..
+                    } = field;
+                    quote! {
+                        #[serde(rename = #name, #(alias = #input_keys),*)]
+                        #field_name: Option<::power_reqwest::__private::serde_json::Value>
+                    }
+                } else {
+                    tokens.clone()
+                }
+            });
+        let assigns = fields.iter().map(|field| {
+            let field_name = &field.field_name;
+            if let Some(path) = &field.json_path {
+                let pointer = path.to_pointer();
+                let missing = if field.optional.is_some() {
+                    quote!(None)
+                } else if let Some(message) = field.require_message() {
+                    quote!(return Err(D::Error::custom(#message)))
+                } else if serde_defaults && field.expr.is_some() {
+                    let default_fn = default_fn_name(field_name);
+                    quote!(#default_fn())
+                } else {
+                    let path = pointer.replace('/', ".");
+                    let path = path.trim_start_matches('.');
+                    quote!(return Err(D::Error::missing_field(#path)))
+                };
+                let found = decode(field);
+                quote! {
+                    #field_name: match value.pointer(#pointer).cloned() {
+                        Some(v) => #found,
+                        None => #missing,
+                    }
+                }
+            } else if let Some(message) = field.require_message() {
+                let found = decode(field);
+                quote! {
+                    #field_name: match plain.#field_name {
+                        Some(v) => #found,
+                        None => return Err(D::Error::custom(#message)),
+                    }
+                }
+            } else {
+                quote!(#field_name: plain.#field_name)
+            }
+        });
+        let derive = serialize.map(|serialize| quote!(#[derive(#serialize)] #serde_crate));
+        (
+            derive.unwrap_or_default(),
+            Some(quote! {
+                // nested fields are picked by json pointers, the others through
+                // a plain shadow struct, required ones are checked after
+                impl<'de> ::power_reqwest::__private::serde::Deserialize<'de> for #name {
+                    fn deserialize<D: ::power_reqwest::__private::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                        use ::power_reqwest::__private::serde::de::Error;
+                        #[derive(::power_reqwest::__private::serde::Deserialize)]
+                        #serde_crate
+                        struct Plain {
+                            #(#plain_fields),*
+                        }
+                        let value = <::power_reqwest::__private::serde_json::Value as ::power_reqwest::__private::serde::Deserialize>::deserialize(deserializer)?;
+                        let plain: Plain = ::power_reqwest::__private::serde_json::from_value(value.clone()).map_err(D::Error::custom)?;
+                        Ok(Self {
+                            #(#assigns),*
+                        })
+                    }
+                }
+            }),
+        )
+    } else {
+        let derives = serialize
+            .into_iter()
+            .chain([quote!(::power_reqwest::__private::serde::Deserialize)]);
+        (quote!(#[derive(#(#derives),*)] #serde_crate), None)
+    };
+
+    let struct_fields = if deserialize.is_some() && !direction.serializes() {
+        &bare_fields
+    } else {
+        &fields_in_struct
+    };
+
     quote! {
-        #[derive(serde::Serialize, serde::Deserialize)]
+        #derive
+        #(#attrs)*
         pub struct #name {
-            #(#fields_in_struct),*
+            #(#struct_fields),*
         }
+        #deserialize
         impl Default for #name {
             fn default() -> Self {
                 Self {
@@ -337,57 +2884,442 @@ fn make_object_struct(name: &Ident, fields: &Vec<Field>) -> TokenStream {
                 }
             }
         }
+        #example_fn
         #(#serde_formatters)*
+        #int_or_str_fns
+        #(#default_fns)*
+    }
+}
+
+impl Field {
+    fn require_message(&self) -> Option<String> {
+        self.require
+            .map(|_| format!("missing required field `{}`", self.name.value()))
+    }
+}
+
+impl JsonPath {
+    // as a json pointer, e.g. `/data/items/0`
+    pub(crate) fn to_pointer(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                JsonPathSegment::Key(key) => {
+                    format!("/{}", key.value().replace('~', "~0").replace('/', "~1"))
+                }
+                JsonPathSegment::Index(index) => format!("/{}", index.base10_digits()),
+            })
+            .collect()
+    }
+}
+
+impl Field {
+    pub(crate) fn to_field_type(&self) -> syn::Type {
+        let mut field_type = if self.cow {
+            syn::parse_quote!(std::borrow::Cow<'static, str>)
+        } else if let Some(typ) = &self.typ {
+            typ.to_type()
+        } else {
+            syn::Path::from_ident(("String", self.name.span())).to_type()
+        };
+        if self.optional.is_some() {
+            let mut option = syn::Path::from_ident(("Option", field_type.span()));
+            option.push_arg(0, field_type);
+            field_type = option.to_type()
+        }
+        field_type
+    }
+}
+
+impl ApiRequest {
+    // wire names of `secret` fields, and of fields set from `secret` client
+    // options
+    fn secret_keys(&self, options: &HashMap<Ident, Field>) -> Vec<String> {
+        fn collect(fields: &[Field], options: &HashMap<Ident, Field>, keys: &mut Vec<String>) {
+            for field in fields.iter() {
+                let from_secret = match &field.expr {
+                    Some(Expr::Variable(var)) if var.client_option => options
+                        .get(&var.name)
+                        .is_some_and(|option| option.secret.is_some()),
+                    _ => false,
+                };
+                if field.secret.is_some() || from_secret {
+                    keys.push(field.name.value());
+                }
+                let mut typ = field.typ.as_ref();
+                while let Some(Type::List(ListType { element_type, .. })) = typ {
+                    typ = Some(element_type);
+                }
+                if let Some(Type::Object(obj)) = typ {
+                    collect(&obj.fields, options, keys);
+                }
+            }
+        }
+        let mut keys = vec![];
+        for config in self.configs() {
+            collect(&config.fields, options, &mut keys);
+        }
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    // structs sent with the request, where declared limits are checked
+    pub(crate) fn configs(&self) -> Vec<&BracedConfig> {
+        let mut configs = vec![];
+        if let Some(data) = &self.data {
+            if let Some(data_match) = &data.data_match {
+                configs.extend(data_match.arms.iter().map(|arm| &arm.data));
+            } else {
+                configs.push(&data.data);
+            }
+        }
+        configs.extend(self.query.iter());
+        configs.extend(self.header.iter());
+        configs
+    }
+
+    fn has_limits(&self) -> bool {
+        self.configs()
+            .iter()
+            .any(|config| config.fields.iter().any(|field| field.has_limits()))
+    }
+}
+
+impl Field {
+    fn has_limits(&self) -> bool {
+        self.typ
+            .as_ref()
+            .map(|typ| typ.has_limits())
+            .unwrap_or(false)
+    }
+
+    // checks `value`, a reference to this field's value
+    fn gen_check(&self, value: TokenStream, error_name: &Ident) -> Option<TokenStream> {
+        let check = self
+            .typ
+            .as_ref()?
+            .gen_check(&quote!(value), &self.name, error_name)?;
+        Some(if self.optional.is_some() {
+            quote! {
+                if let Some(value) = #value {
+                    #check
+                }
+            }
+        } else {
+            quote! {
+                let value = #value;
+                #check
+            }
+        })
+    }
+}
+
+impl Type {
+    fn has_limits(&self) -> bool {
+        match self {
+            Self::Integer(IntegerType { limits, .. }) => limits.is_some(),
+            Self::Float(FloatType { limits, .. }) => limits.is_some(),
+            Self::List(ListType { element_type, .. }) => element_type.has_limits(),
+            Self::Object(obj) => obj.fields.iter().any(|field| field.has_limits()),
+            _ => false,
+        }
+    }
+
+    fn gen_check(
+        &self,
+        value: &TokenStream,
+        name: &LitStr,
+        error_name: &Ident,
+    ) -> Option<TokenStream> {
+        let in_limits = |conditions: Vec<TokenStream>, limits: String| {
+            let message = format!("{{}} is out of {}", limits);
+            quote! {
+                if !(#(#conditions)||*) {
+                    return Err(#error_name {
+                        field: #name,
+                        message: format!(#message, #value),
+                    });
+                }
+            }
+        };
+        match self {
+            Self::Integer(IntegerType {
+                limits: Some(IntLimits { limits, .. }),
+                ..
+            }) => {
+                let conditions = limits
+                    .iter()
+                    .map(|limit| match limit {
+                        IntLimit::Range(range) => quote!((#range).contains(#value)),
+                        IntLimit::Opt(opt) => quote!(*#value == #opt),
+                    })
+                    .collect();
+                let limits = limits
+                    .iter()
+                    .map(|limit| match limit {
+                        IntLimit::Range(range) => {
+                            range.to_token_stream().to_string().replace(' ', "")
+                        }
+                        IntLimit::Opt(opt) => opt.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Some(in_limits(conditions, limits))
+            }
+            Self::Float(FloatType {
+                limits: Some(FloatLimits { limits, .. }),
+                ..
+            }) => {
+                let conditions = limits
+                    .iter()
+                    .map(|range| quote!((#range).contains(#value)))
+                    .collect();
+                let limits = limits
+                    .iter()
+                    .map(|range| range.to_token_stream().to_string().replace(' ', ""))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Some(in_limits(conditions, limits))
+            }
+            Self::List(ListType { element_type, .. }) => {
+                let check = element_type.gen_check(&quote!(element), name, error_name)?;
+                Some(quote! {
+                    for element in #value.iter() {
+                        #check
+                    }
+                })
+            }
+            Self::Object(obj) => {
+                if obj.fields.iter().any(|field| field.has_limits()) {
+                    Some(quote!(#value.validate()?;))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn gen_validates(&self, error_name: &Ident) -> Vec<TokenStream> {
+        match self {
+            Self::List(ListType { element_type, .. }) => element_type.gen_validates(error_name),
+            Self::Object(obj) => gen_validate(&obj.struct_name, &obj.fields, error_name),
+            _ => vec![],
+        }
+    }
+}
+
+// `validate()` for a struct with limited fields, and for its nested objects
+fn gen_validate(name: &Ident, fields: &[Field], error_name: &Ident) -> Vec<TokenStream> {
+    let mut validates = fields
+        .iter()
+        .filter_map(|field| field.typ.as_ref())
+        .flat_map(|typ| typ.gen_validates(error_name))
+        .collect::<Vec<_>>();
+    let checks = fields
+        .iter()
+        .filter_map(|field| {
+            let field_name = &field.field_name;
+            field.gen_check(quote!(&self.#field_name), error_name)
+        })
+        .collect::<Vec<_>>();
+    if !checks.is_empty() {
+        validates.insert(
+            0,
+            quote! {
+                impl #name {
+                    pub fn validate(&self) -> Result<(), #error_name> {
+                        #({ #checks })*
+                        Ok(())
+                    }
+                }
+            },
+        );
     }
+    validates
 }
 
 impl BracedConfig {
-    fn gen_obj_structs(&self) -> Vec<TokenStream> {
+    fn gen_validate(&self, error_name: &Ident) -> Vec<TokenStream> {
+        gen_validate(&self.struct_name, &self.fields, error_name)
+    }
+
+    fn gen_obj_structs(&self, serde_defaults: bool, direction: SerdeDirection) -> Vec<TokenStream> {
         let mut types = self
             .fields
             .iter()
             .filter_map(|f| {
                 if let Some(typ) = &f.typ {
-                    typ.gen_obj_structs()
+                    typ.gen_obj_structs(direction)
                 } else {
                     None
                 }
             })
             .flatten()
             .collect::<Vec<_>>();
-        types.insert(0, make_object_struct(&self.struct_name, &self.fields));
+        types.insert(
+            0,
+            make_object_struct(
+                &self.struct_name,
+                &self.attrs,
+                &self.fields,
+                serde_defaults,
+                direction,
+            ),
+        );
 
         types
     }
 }
 
 impl Type {
-    fn gen_obj_structs(&self) -> Option<Vec<TokenStream>> {
+    fn gen_obj_structs(&self, direction: SerdeDirection) -> Option<Vec<TokenStream>> {
         match self {
-            Self::Object(obj) => Some(obj.gen_obj_structs()),
+            Self::Object(obj) => Some(obj.gen_obj_structs(direction)),
             Self::JsonText(JsonStringType { typ, .. }) => {
                 if let Type::Object(obj) = typ.as_ref() {
-                    Some(obj.gen_obj_structs())
+                    Some(obj.gen_obj_structs(direction))
                 } else {
                     None
                 }
             }
-            Self::List(ListType { element_type, .. }) => element_type.gen_obj_structs(),
+            Self::List(ListType { element_type, .. }) => element_type.gen_obj_structs(direction),
+            Self::Enum(e) => Some(vec![e.gen_enum()]),
             _ => None,
         }
     }
 }
 
+impl EnumType {
+    fn gen_enum(&self) -> TokenStream {
+        if !self.variant_fields.is_empty() {
+            return self.gen_struct_variant_enum();
+        }
+        let Self {
+            enum_name,
+            variants,
+            ..
+        } = self;
+        let (names, variants): (Vec<_>, Vec<_>) = variants.iter().cloned().unzip();
+        let first = &names[0];
+        quote! {
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            pub enum #enum_name {
+                #(#names,)*
+                // keeps codes added on the server side after this client
+                Unknown(String),
+            }
+            impl #enum_name {
+                pub fn as_str(&self) -> &str {
+                    match self {
+                        #(Self::#names => #variants,)*
+                        Self::Unknown(value) => value,
+                    }
+                }
+            }
+            impl Default for #enum_name {
+                fn default() -> Self {
+                    Self::#first
+                }
+            }
+            impl std::str::FromStr for #enum_name {
+                type Err = std::convert::Infallible;
+                fn from_str(value: &str) -> Result<Self, Self::Err> {
+                    Ok(match value {
+                        #(#variants => Self::#names,)*
+                        _ => Self::Unknown(value.to_owned()),
+                    })
+                }
+            }
+            impl std::fmt::Display for #enum_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(self.as_str())
+                }
+            }
+            impl ::power_reqwest::__private::serde::Serialize for #enum_name {
+                fn serialize<S: ::power_reqwest::__private::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.serialize_str(self.as_str())
+                }
+            }
+            impl<'de> ::power_reqwest::__private::serde::Deserialize<'de> for #enum_name {
+                fn deserialize<D: ::power_reqwest::__private::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let value = <String as ::power_reqwest::__private::serde::Deserialize>::deserialize(deserializer)?;
+                    Ok(match value.as_str() {
+                        #(#variants => Self::#names,)*
+                        _ => Self::Unknown(value),
+                    })
+                }
+            }
+        }
+    }
+
+    // unknown codes fail to deserialize, serde has no catch-all for them
+    fn gen_struct_variant_enum(&self) -> TokenStream {
+        let Self {
+            enum_name,
+            variants,
+            variant_fields,
+            json_case,
+            ..
+        } = self;
+        let rename_all_fields = json_case
+            .as_ref()
+            .map(|case| quote!(#[serde(rename_all_fields = #case)]));
+        let variant_decls = variants.iter().map(|(name, code)| {
+            let fields = variant_fields.get(name).map(|fields| {
+                let fields = fields.iter().map(|(field, typ)| {
+                    let typ = typ.to_type();
+                    quote!(#field: #typ)
+                });
+                quote!({ #(#fields,)* })
+            });
+            quote! {
+                #[serde(rename = #code)]
+                #name #fields
+            }
+        });
+        let (first, _) = &variants[0];
+        let default = variant_fields.get(first).map(|fields| {
+            let fields = fields.iter().map(|(field, _)| field);
+            quote!({ #(#fields: Default::default(),)* })
+        });
+        let serde_crate = serde_crate();
+        quote! {
+            #[derive(Clone, Debug, PartialEq, ::power_reqwest::__private::serde::Serialize, ::power_reqwest::__private::serde::Deserialize)]
+            #serde_crate
+            #rename_all_fields
+            pub enum #enum_name {
+                #(#variant_decls,)*
+            }
+            impl Default for #enum_name {
+                fn default() -> Self {
+                    Self::#first #default
+                }
+            }
+        }
+    }
+}
+
 impl ObjectType {
-    fn gen_obj_structs(&self) -> Vec<TokenStream> {
+    fn gen_obj_structs(&self, direction: SerdeDirection) -> Vec<TokenStream> {
         let mut types = self
             .fields
             .iter()
-            .filter_map(|f| f.typ.as_ref().map(|t| t.gen_obj_structs()))
+            .filter_map(|f| f.typ.as_ref().map(|t| t.gen_obj_structs(direction)))
             .flatten()
             .flatten()
             .collect::<Vec<_>>();
-        types.insert(0, make_object_struct(&self.struct_name, &self.fields));
+        types.insert(
+            0,
+            make_object_struct(
+                &self.struct_name,
+                &self.attrs,
+                &self.fields,
+                false,
+                direction,
+            ),
+        );
         types
     }
 }
@@ -397,10 +3329,12 @@ impl DateTimeFormat {
         let Self {
             format, mod_name, ..
         } = self;
+        // a struct going one way only uses one of the two functions
         quote! {
+            #[allow(dead_code)]
             mod #mod_name {
-                use chrono::{DateTime, Utc, NaiveDateTime};
-                use serde::{self, Deserialize, Serializer, Deserializer};
+                use ::power_reqwest::__private::chrono::{DateTime, Utc, NaiveDateTime};
+                use ::power_reqwest::__private::serde::{self, Deserialize, Serializer, Deserializer};
 
 
                 pub fn serialize<S>(
@@ -421,7 +3355,7 @@ impl DateTimeFormat {
                     D: Deserializer<'de>,
                 {
                     let s = String::deserialize(deserializer)?;
-                    let dt = NaiveDateTime::parse_from_str(&s, #format).map_err(serde::de::Error::custom)?;
+                    let dt = NaiveDateTime::parse_from_str(&s, #format).map_err(::power_reqwest::__private::serde::de::Error::custom)?;
                     Ok(DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc))
                 }
             }
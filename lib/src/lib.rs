@@ -3,6 +3,14 @@ mod expand;
 mod model;
 mod parse;
 mod text_parsers;
+#[cfg(feature = "serde")]
+mod view;
 
 pub use model::*;
 pub use text_parsers::*;
+#[cfg(feature = "serde")]
+pub use view::*;
+
+pub fn parse_client(tokens: proc_macro2::TokenStream) -> syn::Result<Client> {
+    syn::parse2(tokens)
+}
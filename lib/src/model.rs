@@ -5,12 +5,85 @@ use syn::{
     parse::ParseStream,
     punctuated::Punctuated,
     token::{Brace, Bracket, Paren},
-    ExprRange, Ident, LitBool, LitFloat, LitInt, LitStr, Token,
+    Attribute, ExprRange, Ident, LitBool, LitFloat, LitInt, LitStr, Token,
 };
 
 #[derive(Clone, Debug)]
 pub struct Client {
     pub name: Ident,
+    pub type_affix: TypeAffix,
+    // `strict: true` rejects blocks that would generate zero-field structs
+    pub strict: bool,
+    // `canonical_headers: sorted` orders request headers by lowercased name,
+    // as required by some signing schemes
+    pub sort_headers: bool,
+    // `field_order: sorted` orders request query and body fields by name, for
+    // signing schemes over ordered params
+    pub sort_fields: bool,
+    // `string_type: cow` generates `string` and string constant fields as
+    // `Cow<'static, str>`: literals and constants are borrowed, arguments and
+    // decoded responses are still owned strings, nothing borrows from the
+    // caller's input
+    pub cow_strings: bool,
+    // `empty_lists: skip` leaves empty list fields out of request structs
+    pub skip_empty_lists: bool,
+    // `default_int_width: 32` maps bare `uint`/`int`/`integer` to `u32`/`i32`
+    // instead of `u64`/`i64`
+    pub narrow_ints: bool,
+    // `header_prefix: "X-Ca-"` is prepended to custom request header names
+    pub header_prefix: Option<LitStr>,
+    // `args: struct` takes request data as one struct argument instead of
+    // one argument per variable
+    pub struct_args: bool,
+    // `allow_dead_code: true` puts every generated item in a module marked
+    // `#[allow(dead_code)]`, for clients whose types are not all used
+    pub allow_dead_code: bool,
+    // `validate_only: true` runs every check but emits no code, for quick
+    // feedback while editing
+    pub validate_only: bool,
+    // `emit_tests: true` adds a `#[cfg(test)]` smoke test per api, serializing
+    // its request structs built from examples
+    pub emit_tests: bool,
+    // `debug: true` prints every request to stderr in debug builds, with the
+    // values of `secret` fields masked
+    pub debug_requests: bool,
+    // `method_case: verbatim` keeps api names as declared, e.g. `AddSmsSign`
+    pub verbatim_method_names: bool,
+    // `json_case: PascalCase` names the fields of enum struct variants on the
+    // wire, as serde's `rename_all_fields`
+    pub json_case: Option<LitStr>,
+    // `error_type: crate::MyError`, shared by several clients instead of a
+    // generated error type
+    pub error_type: Option<syn::Path>,
+    // `module: sms_api` wraps every generated item in `pub mod sms_api`
+    pub module: Option<Ident>,
+    // `capture_body_on_error: true` reads responses as text before decoding,
+    // so status and decode errors carry the raw body
+    pub capture_body_on_error: bool,
+    // `response_arc: true` returns decoded response data as `Arc<T>`, to be
+    // shared across tasks
+    pub response_arc: bool,
+    // `accept_encoding: "gzip, br"`, sent with every request
+    pub accept_encoding: Option<LitStr>,
+    // `decompress: manual` turns off reqwest's own response decompression,
+    // the generated code decodes gzip, brotli and deflate bodies itself, also
+    // gzip bodies sent without a `Content-Encoding`
+    pub manual_decompress: bool,
+    // `cookies: true` keeps the cookies set by responses and sends them with
+    // later requests, needs reqwest's `cookies` feature
+    pub cookie_store: bool,
+    pub retry: Option<RetryPolicy>,
+    // `timeout: 30000` or `timeout: "30s"`, in milliseconds, applied to every
+    // request
+    pub timeout: Option<LitInt>,
+    // `base_urls: ["https://a", "https://b"]` prefixes relative api uris, the
+    // next host is tried when connecting to one fails
+    pub base_urls: Vec<LitStr>,
+    // `max_apis: 40` warns at the first api past this count, big invocations
+    // slow down incremental builds
+    pub max_apis: Option<LitInt>,
+    // the first api past `max_apis`
+    pub too_many_apis: Option<Ident>,
     pub options: Option<BracedConfig>,
     pub option_map: HashMap<Ident, Field>,
     pub hooks: Option<Hooks>,
@@ -18,10 +91,39 @@ pub struct Client {
     pub templates: HashMap<Ident, DataTemplate>,
 }
 
+// `type_prefix: X` and `type_suffix: Y` wrap every generated type name
+#[derive(Clone, Debug, Default)]
+pub struct TypeAffix {
+    pub prefix: Option<Ident>,
+    pub suffix: Option<Ident>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Hooks {
     pub(crate) span: Span,
+    // `on_submit: sign` is called as
+    // `fn(reqwest::Request) -> Result<reqwest::Request, E>` on the built
+    // request before it is sent, to sign it, its error is converted into the
+    // method's error
     pub on_submit: Option<syn::Path>,
+    // `on_response: check_response` is called as
+    // `fn(reqwest::Response) -> Result<reqwest::Response, E>` right after the
+    // request is sent, its error is converted into the method's error
+    pub on_response: Option<syn::Path>,
+}
+
+// `retry: { attempts: 3, delay: "200ms", status: [429, 503] }` sends a request
+// again when the transport fails or one of the statuses is answered, after
+// `delay * 2^n` or as asked by `Retry-After`; sleeps with tokio's `time`
+// feature
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub span: Span,
+    // including the first one
+    pub attempts: LitInt,
+    // in milliseconds
+    pub delay: LitInt,
+    pub status: Vec<LitInt>,
 }
 
 #[derive(Clone, Debug)]
@@ -45,6 +147,10 @@ pub struct Api {
     pub request: ApiRequest,
     pub response: Option<ApiResponse>,
     pub variables: Vec<Variable>,
+    // `blocking get ...` also generates a sync `<api>_blocking`, run on a
+    // current-thread runtime shared by the client's blocking methods; like
+    // tokio's `block_on` it panics when called from async code
+    pub blocking: Option<Span>,
 }
 
 #[derive(Clone, Debug)]
@@ -93,28 +199,141 @@ pub struct ApiRequest {
     pub query: Option<BracedConfig>,
     pub query_var: Option<Ident>,
     pub data: Option<ApiRequestData>,
+    // `auth: basic($user, $pass)`, takes precedence over credentials in the url
+    pub auth: Option<BasicAuth>,
+    // `accept: "text/csv"`
+    pub accept: Option<LitStr>,
+    // `transfer: chunked|length`, for a `bytes` body
+    pub transfer: Option<Transfer>,
+    // `key: [phone_number, template_code]`
+    pub key: Option<RequestKey>,
+    // `bytes = $payload`: a `bytes::Bytes` body, sent without copying
+    pub bytes_body: Option<BytesBody>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BytesBody {
+    pub token: Span,
+    pub var: Ident,
+}
+
+#[derive(Clone, Debug)]
+pub struct RequestKey {
+    pub span: Span,
+    pub bracket: Bracket,
+    pub names: Vec<Ident>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Transfer {
+    // stream the body, sent with `Transfer-Encoding: chunked`
+    Chunked(Span),
+    // sent whole, with an explicit `Content-Length`
+    Length(Span),
+}
+
+#[derive(Clone, Debug)]
+pub struct BasicAuth {
+    pub span: Span,
+    pub user: Expr,
+    pub passwd: Option<Expr>,
 }
 
 #[derive(Clone, Debug)]
 pub struct ApiRequestData {
     pub data_type: DataType,
+    pub encoding: Option<UrlEncoding>,
     pub data: BracedConfig,
     pub data_var: Option<Ident>,
+    // `json? { ... }`: the data is an `Option<_>` argument, the body is only
+    // attached when it is `Some`
+    pub optional: Option<Span>,
+    // `json [{ ... }] = $items`: the body is a top-level array, taken as a
+    // `Vec` of the data struct
+    pub array: Option<Bracket>,
+    // `json match $kind { A => {...}, B => {...} }`, `data` is left empty
+    pub data_match: Option<DataMatch>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DataMatch {
+    pub token: Span,
+    pub var: Ident,
+    pub enum_name: Ident,
+    pub brace: Brace,
+    pub arms: Vec<DataArm>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DataArm {
+    pub name: Ident,
+    pub data: BracedConfig,
+    // carried by the enum variant instead of the api arguments
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Clone, Debug)]
+pub enum UrlEncoding {
+    // application/x-www-form-urlencoded: spaces as `+`
+    Form(Span),
+    // RFC 3986: spaces as `%20`, required by signing schemes like aliyun's
+    Rfc3986(Span),
 }
 
 #[derive(Clone, Debug)]
 pub enum DataType {
     Json(Span),
+    // sent like `Urlencoded`, without its encoding option
     Form(Span),
+    // lists and objects in the body are sent as `List.1.Name=x` pairs
     Urlencoded(Span),
+    // `multipart/form-data`, request bodies only, needs reqwest's `multipart`
+    // feature
+    Multipart(Span),
 }
 
 #[derive(Clone, Debug)]
 pub struct ApiResponse {
+    // `-> ?{ ... }`: an empty body is accepted and decoded as `None`
+    pub optional: Option<Span>,
     pub brace: Brace,
     pub header: Option<BracedConfig>,
     pub cookie: Option<BracedConfig>,
     pub data: Option<ApiResponseData>,
+    // `-> { text }`: the body is returned as `String`, excludes `data`
+    pub text: Option<Span>,
+    pub page_info: Option<PageInfo>,
+    pub success_status: Option<SuccessStatus>,
+    // `rate_limit_info`: the `X-RateLimit-*` headers are read into a struct
+    pub rate_limit_info: Option<Span>,
+}
+
+// `success_status: [200, 202]`: replaces the default 2xx check
+#[derive(Clone, Debug)]
+pub struct SuccessStatus {
+    pub span: Span,
+    pub bracket: Bracket,
+    pub codes: Vec<LitInt>,
+}
+
+// `page_info { total: TotalCount, page: CurrentPage, size: PageSize }`,
+// resolved to the field names of the response data
+#[derive(Clone, Debug)]
+pub struct PageInfo {
+    pub span: Span,
+    pub total: Ident,
+    pub page: Ident,
+    pub size: Ident,
+    // `items: Records, next: $page`
+    pub stream: Option<PageStream>,
+}
+
+// an `<api>_stream` method yielding the items of every page: the list field
+// holding them, and the argument the next page number is passed in
+#[derive(Clone, Debug)]
+pub struct PageStream {
+    pub items: Ident,
+    pub next: Ident,
 }
 
 #[derive(Clone, Debug)]
@@ -135,6 +354,8 @@ pub struct BracedConfig {
     pub extend: Option<Ident>,
     pub struct_name: Ident,
     pub brace: Brace,
+    // `#![...]` at the head of block, emitted on the generated struct
+    pub attrs: Vec<Attribute>,
     pub fields: Vec<Field>,
     pub removed_fields: HashSet<LitStr>,
 }
@@ -151,6 +372,9 @@ pub enum Type {
     JsonText(JsonStringType),
     Map(Span),
     List(ListType),
+    Enum(EnumType),
+    // `file`: a multipart file part, given as its file name and bytes
+    File(FileType),
 }
 
 impl Type {
@@ -169,6 +393,8 @@ impl Type {
                 bracket: list.bracket,
                 element_type: Box::new(list.element_type.pure()),
             }),
+            Type::Enum(e) => Type::Enum(e.clone()),
+            Type::File(file) => Type::File(file.clone()),
         }
     }
     pub fn is_string(&self) -> bool {
@@ -203,6 +429,8 @@ impl PartialEq<Type> for Type {
             (Self::List(l0), Type::List(r0)) => {
                 l0.element_type.as_ref().eq(r0.element_type.as_ref())
             }
+            (Self::Enum(l0), Type::Enum(r0)) => l0.enum_name.eq(&r0.enum_name),
+            (Self::File(_), Type::File(_)) => true,
             _ => false,
         }
     }
@@ -244,6 +472,13 @@ pub struct FloatLimits {
     pub limits: Punctuated<ExprRange, Token![,]>,
 }
 
+// `file("image/png")`: the mime type is sent as the part's Content-Type
+#[derive(Clone, Debug)]
+pub struct FileType {
+    pub span: Span,
+    pub mime: Option<LitStr>,
+}
+
 #[derive(Clone, Debug)]
 pub struct DateTimeType {
     pub span: Span,
@@ -280,37 +515,85 @@ pub struct ListType {
     pub element_type: Box<Type>,
 }
 
+// `enum("A", "B")`: string codes, unknown codes are kept in `Unknown(String)`
+//
+// `enum("text" { content: string }, "none")`: struct variants, externally
+// tagged as in `{"text": {"content": ".."}}`, there is no `Unknown` then
+#[derive(Clone, Debug)]
+pub struct EnumType {
+    pub token: Span,
+    pub paren: Paren,
+    pub enum_name: Ident,
+    // variant name and the code it stands for
+    pub variants: Vec<(Ident, LitStr)>,
+    // fields of the struct variants, by variant name
+    pub variant_fields: HashMap<Ident, VariantFields>,
+    // the client `json_case`
+    pub json_case: Option<LitStr>,
+}
+
+// field names and types of a struct variant
+pub type VariantFields = Vec<(Ident, Type)>;
+
 #[derive(Clone, Debug)]
 pub struct ObjectType {
     pub struct_name: Ident,
     pub brace: Brace,
+    pub attrs: Vec<Attribute>,
     pub fields: Vec<Field>,
 }
 
 impl ObjectType {
+    // the field a key of an object constant sets, by its declared name
+    pub fn field_of_key(&self, key: &Ident) -> Option<&Field> {
+        self.fields.iter().find(|field| key.eq(&field.name.value()))
+    }
+
     pub fn pure(&self) -> ObjectType {
         ObjectType {
             struct_name: self.struct_name.clone(),
             brace: self.brace,
+            attrs: self.attrs.clone(),
             fields: self
                 .fields
                 .iter()
                 .map(
                     |Field {
                          name,
+                         input_keys,
                          field_name,
                          optional,
                          typ,
                          default,
+                         readonly,
+                         writeonly,
+                         example,
+                         require,
+                         secret,
+                         int_or_str,
+                         attrs,
                          ..
                      }| Field {
                         name: name.clone(),
+                        input_keys: input_keys.clone(),
                         field_name: field_name.clone(),
                         optional: optional.clone(),
                         typ: typ.as_ref().map(|typ| typ.pure()),
+                        readonly: *readonly,
+                        writeonly: *writeonly,
+                        example: example.clone(),
+                        require: *require,
+                        secret: *secret,
+                        int_or_str: *int_or_str,
                         alias: None,
                         expr: None,
+                        condition: None,
+                        json_path: None,
+                        cow: false,
+                        stringify: false,
+                        skip_empty: false,
                         default: default.clone(),
+                        attrs: attrs.clone(),
                     },
                 )
                 .collect(),
@@ -327,12 +610,40 @@ impl PartialEq<ObjectType> for ObjectType {
 #[derive(Clone, Debug)]
 pub struct Field {
     pub name: LitStr,
+    // `Name <- ["Name", "OldName"]`: other keys accepted when deserializing
+    pub input_keys: Vec<LitStr>,
     pub field_name: Ident,
     pub optional: Option<Span>,
     pub typ: Option<Type>,
+    // `readonly`: deserialized only, never sent
+    pub readonly: Option<Span>,
+    // `writeonly`: sent only, never deserialized
+    pub writeonly: Option<Span>,
+    // `example = "..."`: documented and used by the generated `example()`
+    pub example: Option<Constant>,
+    // `require`: a response field decoded as optional first, so a missing
+    // value fails with an error naming the field
+    pub require: Option<Span>,
+    // `secret`: masked when `debug: true` prints requests
+    pub secret: Option<Span>,
+    // `int_or_str`: an integer or enum field decoded from both `1` and `"1"`
+    pub int_or_str: Option<Span>,
     pub alias: Option<Ident>,
     pub expr: Option<Expr>,
+    // `= $$x if $$flag`: sent only when the bool client option is set
+    pub condition: Option<Variable>,
+    // `= $.data.access_token` on a response field: read from a nested path
+    pub json_path: Option<JsonPath>,
+    // a `string` or string constant field generated as `Cow<'static, str>`,
+    // borrowed only from literals
+    pub cow: bool,
+    // a string header set from a non-string argument, converted on init
+    pub stringify: bool,
+    // a request list field left out when empty
+    pub skip_empty: bool,
     pub default: Option<syn::Expr>,
+    // trailing `#[...]`, emitted on the generated field
+    pub attrs: Vec<Attribute>,
 }
 
 #[derive(Clone, Debug)]
@@ -350,16 +661,33 @@ pub enum Expr {
     Datetime(DatetimeFn),
     Timestamp(UnixTimestampUintFn),
     Join(JoinStringFn),
+    Transform(StringTransformFn),
     Or(OrExpr),
     Default(Span),
 }
 
+#[derive(Clone, Debug)]
+pub struct JsonPath {
+    pub span: Span,
+    pub segments: Vec<JsonPathSegment>,
+}
+
+#[derive(Clone, Debug)]
+pub enum JsonPathSegment {
+    Key(LitStr),
+    Index(LitInt),
+}
+
 #[derive(Clone, Debug)]
 pub struct Variable {
     pub dollar: Span,
     pub name: Ident,
     pub typ: Option<Type>,
     pub client_option: bool,
+    // `$var?`, or assigned directly to an optional field: an `Option<T>` argument
+    pub optional: Option<Span>,
+    // the argument is moved here but also used elsewhere, so it is cloned
+    pub shared: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -427,15 +755,11 @@ pub struct ConstantArray {
 
 impl PartialEq for ConstantArray {
     fn eq(&self, other: &Self) -> bool {
-        if self.elements.len() != other.elements.len() {
-            return false;
-        }
-        if self.elements.len() == 0 {
-            return true;
+        // same element type, whatever the lengths; `[]` fits any array
+        match (self.elements.first(), other.elements.first()) {
+            (Some(el1), Some(el2)) => el1.eq(el2),
+            _ => true,
         }
-        let el1 = self.elements.first().unwrap();
-        let el2 = other.elements.first().unwrap();
-        el1.eq(el2)
     }
 }
 
@@ -470,6 +794,22 @@ pub struct JoinStringFn {
     pub sep: LitStr,
 }
 
+// `upper($x)`, `lower($x)` or `trim($x)`
+#[derive(Clone, Debug)]
+pub struct StringTransformFn {
+    pub token: Span,
+    pub paren: Paren,
+    pub variable: Variable,
+    pub transform: StringTransform,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum StringTransform {
+    Upper,
+    Lower,
+    Trim,
+}
+
 #[derive(Clone, Debug)]
 pub struct UnixTimestampUintFn {
     pub token: Span,
@@ -2,12 +2,13 @@ use std::collections::{HashMap, HashSet};
 
 use convert_case::{Case, Casing};
 use proc_macro2::Span;
+use quote::ToTokens;
 use syn::{
     parse::{discouraged::Speculative, Parse, ParseBuffer, ParseStream},
     punctuated::Punctuated,
     spanned::Spanned,
-    token::Paren,
-    ExprRange, Ident, LitStr, Token,
+    token::{Brace, Bracket, Paren},
+    AttrStyle, Attribute, ExprRange, Ident, LitInt, LitStr, Token,
 };
 use syn_prelude::{
     ForkWithParsible, ParseAsIdent, ParseAsLitStr, PathHelpers, ToErr, ToExpr, ToIdent,
@@ -17,17 +18,57 @@ use syn_prelude::{
 
 use crate::{model::*, url_parser::parse_uri_and_update_api};
 
+// the `rename_all` rules of serde
+const JSON_CASES: [&str; 8] = [
+    "lowercase",
+    "UPPERCASE",
+    "PascalCase",
+    "camelCase",
+    "snake_case",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+    "SCREAMING-KEBAB-CASE",
+];
+
 impl Parse for Client {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let whole_span = input.span();
         let mut client = Self {
             name: Ident::new("_", whole_span),
+            type_affix: Default::default(),
+            strict: false,
+            sort_headers: false,
+            sort_fields: false,
+            cow_strings: false,
+            narrow_ints: false,
+            skip_empty_lists: false,
+            header_prefix: None,
+            struct_args: false,
+            allow_dead_code: false,
+            validate_only: false,
+            emit_tests: false,
+            debug_requests: false,
+            verbatim_method_names: false,
+            json_case: None,
+            error_type: None,
+            module: None,
+            capture_body_on_error: false,
+            response_arc: false,
+            accept_encoding: None,
+            manual_decompress: false,
+            cookie_store: false,
+            retry: None,
+            timeout: None,
+            base_urls: vec![],
+            max_apis: None,
+            too_many_apis: None,
             options: Default::default(),
             option_map: Default::default(),
             hooks: None,
             apis: vec![],
             templates: HashMap::new(),
         };
+        let mut seen_options = HashSet::new();
         while !input.is_empty() {
             if input.try_parse_comma().is_some() || input.try_parse_semi().is_some() {
                 continue;
@@ -35,7 +76,8 @@ impl Parse for Client {
 
             if let Some(api) = Api::try_parse(input)? {
                 client.apis.push(api);
-            } else if let Some(_ident) = input.try_parse_as_ident("name", true) {
+            } else if let Some(ident) = input.try_parse_as_ident("name", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
                 input.parse::<Token![:]>()?;
                 let name: Ident = input.parse()?;
                 if !name.to_string().is_case(Case::UpperCamel) {
@@ -43,6 +85,268 @@ impl Parse for Client {
                         .to_err()?;
                 }
                 client.name = name;
+            } else if let Some(ident) = input.try_parse_as_ident("strict", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.strict = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("allow_dead_code", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.allow_dead_code = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("validate_only", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.validate_only = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("debug", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.debug_requests = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("emit_tests", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.emit_tests = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("args", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                if input.peek(Token![struct]) {
+                    input.parse::<Token![struct]>()?;
+                    client.struct_args = true;
+                } else if input.try_parse_as_ident("flat", false).is_some() {
+                    client.struct_args = false;
+                } else {
+                    input
+                        .span()
+                        .to_syn_error("expect 'struct' or 'flat'")
+                        .to_err()?;
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("error_type", true) {
+                if client.error_type.is_some() {
+                    ident
+                        .to_syn_error("duplicated error_type config")
+                        .to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                client.error_type = Some(input.parse()?);
+            } else if let Some(ident) = input.try_parse_as_ident("module", true) {
+                if client.module.is_some() {
+                    ident.to_syn_error("duplicated module config").to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                let module: Ident = input.parse()?;
+                if !module.to_string().is_case(Case::Snake) {
+                    module
+                        .to_syn_error("expect 'snake_case' module name")
+                        .to_err()?;
+                }
+                client.module = Some(module);
+            } else if let Some(ident) = input.try_parse_as_ident("capture_body_on_error", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.capture_body_on_error = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("cookies", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.cookie_store = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("response_arc", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                client.response_arc = input.parse::<syn::LitBool>()?.value;
+            } else if let Some(ident) = input.try_parse_as_ident("accept_encoding", true) {
+                if client.accept_encoding.is_some() {
+                    ident
+                        .to_syn_error("duplicated accept_encoding config")
+                        .to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                let encoding = input.parse::<LitStr>()?;
+                let value = encoding.value();
+                // checked here, the header value is built with `from_static`
+                if value.trim().is_empty()
+                    || !value.chars().all(|c| c == ' ' || c.is_ascii_graphic())
+                {
+                    encoding
+                        .to_syn_error("expect a valid Accept-Encoding value")
+                        .to_err()?;
+                }
+                client.accept_encoding = Some(encoding);
+            } else if let Some(ident) = input.try_parse_as_ident("timeout", true) {
+                if client.timeout.is_some() {
+                    ident.to_syn_error("duplicated timeout config").to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                client.timeout = Some(parse_timeout(input)?);
+            } else if let Some(ident) = input.try_parse_as_ident("max_apis", true) {
+                if client.max_apis.is_some() {
+                    ident.to_syn_error("duplicated max_apis config").to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                let max = input.parse::<LitInt>()?;
+                if max.base10_parse::<usize>()? == 0 {
+                    max.span()
+                        .to_syn_error("expect a positive count")
+                        .to_err()?;
+                }
+                client.max_apis = Some(max);
+            } else if let Some(ident) = input.try_parse_as_ident("decompress", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                if let Some(mode) = input.try_parse_one_of_idents(("auto", "manual")) {
+                    client.manual_decompress = mode.to_string().eq("manual");
+                } else {
+                    input
+                        .span()
+                        .to_syn_error("expect 'auto' or 'manual'")
+                        .to_err()?;
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("json_case", true) {
+                if client.json_case.is_some() {
+                    ident.to_syn_error("duplicated json_case config").to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                // the kebab cases are no idents
+                let case = if input.peek(LitStr) {
+                    input.parse::<LitStr>()?
+                } else {
+                    let ident = input.parse::<Ident>()?;
+                    LitStr::new(&ident.to_string(), ident.span())
+                };
+                if !JSON_CASES.contains(&case.value().as_str()) {
+                    case.to_syn_error(format!("expect one of {}", JSON_CASES.join(", ")))
+                        .to_err()?;
+                }
+                client.json_case = Some(case);
+            } else if let Some(ident) = input.try_parse_as_ident("method_case", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                if let Some(case) = input.try_parse_one_of_idents(("verbatim", "snake")) {
+                    client.verbatim_method_names = case.to_string().eq("verbatim");
+                } else {
+                    input
+                        .span()
+                        .to_syn_error("expect 'verbatim' or 'snake'")
+                        .to_err()?;
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("header_prefix", true) {
+                if client.header_prefix.is_some() {
+                    ident
+                        .to_syn_error("duplicated header_prefix config")
+                        .to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                let prefix = input.parse::<LitStr>()?;
+                if prefix.value().trim().is_empty() {
+                    prefix.to_syn_error("expect a header prefix").to_err()?;
+                }
+                client.header_prefix = Some(prefix);
+            } else if let Some(ident) = input.try_parse_as_ident("base_urls", true) {
+                if !client.base_urls.is_empty() {
+                    ident.to_syn_error("duplicated base_urls config").to_err()?;
+                }
+                input.parse::<Token![:]>()?;
+                let inner: ParseBuffer;
+                let bracket = syn::bracketed!(inner in input);
+                for url in inner.parse_terminated(|input| input.parse::<LitStr>(), Token![,])? {
+                    let value = url.value();
+                    let host = value
+                        .strip_prefix("https://")
+                        .or_else(|| value.strip_prefix("http://"));
+                    // the api uri is appended as is
+                    if !host.is_some_and(|host| !host.is_empty() && !host.ends_with('/')) {
+                        url.to_syn_error(
+                            "expect a 'http://' or 'https://' url without a trailing slash",
+                        )
+                        .to_err()?;
+                    }
+                    if let Some(prev) = client.base_urls.iter().find(|prev| prev.value() == value) {
+                        (url.span(), prev.span())
+                            .to_span()
+                            .to_syn_error("duplicated base url")
+                            .to_err()?;
+                    }
+                    client.base_urls.push(url);
+                }
+                if client.base_urls.is_empty() {
+                    bracket
+                        .span
+                        .join()
+                        .to_syn_error("expect at least one base url")
+                        .to_err()?;
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("field_order", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                if let Some(order) = input.try_parse_one_of_idents(("sorted", "declared")) {
+                    client.sort_fields = order.to_string().eq("sorted");
+                } else {
+                    input
+                        .span()
+                        .to_syn_error("expect 'sorted' or 'declared'")
+                        .to_err()?;
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("string_type", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                if let Some(typ) = input.try_parse_one_of_idents(("cow", "string")) {
+                    client.cow_strings = typ.to_string().eq("cow");
+                } else {
+                    input
+                        .span()
+                        .to_syn_error("expect 'cow' or 'string'")
+                        .to_err()?;
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("empty_lists", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                if let Some(lists) = input.try_parse_one_of_idents(("skip", "keep")) {
+                    client.skip_empty_lists = lists.to_string().eq("skip");
+                } else {
+                    input
+                        .span()
+                        .to_syn_error("expect 'skip' or 'keep'")
+                        .to_err()?;
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("default_int_width", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                let width = input.parse::<LitInt>()?;
+                match width.base10_digits() {
+                    "32" => client.narrow_ints = true,
+                    "64" => client.narrow_ints = false,
+                    _ => width.span().to_syn_error("expect 32 or 64").to_err()?,
+                }
+            } else if let Some(ident) = input.try_parse_as_ident("canonical_headers", true) {
+                check_duplicated_option(&mut seen_options, &ident)?;
+                input.parse::<Token![:]>()?;
+                if let Some(order) = input.try_parse_one_of_idents(("sorted", "declared")) {
+                    client.sort_headers = order.to_string().eq("sorted");
+                } else {
+                    input
+                        .span()
+                        .to_syn_error("expect 'sorted' or 'declared'")
+                        .to_err()?;
+                }
+            } else if let Some(ident) =
+                input.try_parse_one_of_idents(("type_prefix", "type_suffix"))
+            {
+                input.parse::<Token![:]>()?;
+                let affix: Ident = input.parse()?;
+                if !affix.to_string().is_case(Case::UpperCamel) {
+                    affix
+                        .to_syn_error("expect 'UpperCamel' case name")
+                        .to_err()?;
+                }
+                let slot = if ident.to_string().eq("type_prefix") {
+                    &mut client.type_affix.prefix
+                } else {
+                    &mut client.type_affix.suffix
+                };
+                if let Some(prev) = slot {
+                    (ident.span(), prev.span())
+                        .to_span()
+                        .to_syn_error(format!("duplicated {ident} config"))
+                        .to_err()?;
+                }
+                *slot = Some(affix);
             } else if let Some(ident) = input.try_parse_one_of_idents(("params", "options")) {
                 if let Some(params) = &client.options {
                     (ident.span(), params.token)
@@ -80,6 +384,14 @@ impl Parse for Client {
                 } else {
                     client.templates.insert(template.name.clone(), template);
                 }
+            } else if let Some(retry) = RetryPolicy::try_parse(input)? {
+                if client.retry.is_some() {
+                    retry
+                        .span
+                        .to_syn_error("duplicated retry config")
+                        .to_err()?;
+                }
+                client.retry = Some(retry);
             } else if let Some(hooks) = Hooks::try_parse(input)? {
                 if let Some(prev) = &client.hooks {
                     (hooks.span, prev.span)
@@ -97,16 +409,37 @@ impl Parse for Client {
             }
         }
 
+        // checked after all config items, `method_case` may follow the apis
+        if !client.verbatim_method_names {
+            for api in client.apis.iter() {
+                if !api.name.to_string().is_case(Case::Snake) {
+                    api.name
+                        .to_syn_error("method for client expects normal snake-case name")
+                        .to_err()?;
+                }
+            }
+        }
+
+        if let Some(max) = &client.max_apis {
+            client.too_many_apis = client
+                .apis
+                .get(max.base10_parse::<usize>()?)
+                .map(|api| api.name.clone());
+        }
+
         if let Some(options) = client.options.as_mut() {
-            options.struct_name = client.name.with_suffix("Options");
+            options.struct_name = client.type_affix.apply(client.name.with_suffix("Options"));
         }
         client.flatten_templates()?;
 
         for api in client.apis.iter_mut() {
             api.extend_templates(&client.templates)?;
+            api.merge_uri_query()?;
+            api.resolve_field_refs();
         }
 
         client.resolve_object_type_names()?;
+        client.check_formatter_names()?;
 
         for (name, template) in client.templates.iter() {
             let mut extends = vec![];
@@ -122,40 +455,250 @@ impl Parse for Client {
             }
         }
 
+        if client.strict {
+            client.check_empty_structs()?;
+        }
+
+        if let Some(prefix) = &client.header_prefix {
+            for api in client.apis.iter_mut() {
+                if let Some(headers) = &mut api.request.header {
+                    headers.prefix_header_names(prefix);
+                }
+            }
+        }
+
+        if client.sort_headers {
+            for api in client.apis.iter_mut() {
+                if let Some(headers) = &mut api.request.header {
+                    headers
+                        .fields
+                        .sort_by_key(|field| field.name.value().to_lowercase());
+                }
+            }
+        }
+
+        if client.sort_fields {
+            for api in client.apis.iter_mut() {
+                for config in api.request.serialized_configs_mut() {
+                    config.fields.sort_by_key(|field| field.name.value());
+                }
+            }
+        }
+
+        if client.cow_strings {
+            for api in client.apis.iter_mut() {
+                let mut configs = api.request.serialized_configs_mut();
+                if let Some(data) = api.response.as_mut().and_then(|r| r.data.as_mut()) {
+                    configs.push(&mut data.data);
+                }
+                for config in configs {
+                    mark_cow_strings(&mut config.fields);
+                }
+            }
+        }
+
+        if client.skip_empty_lists {
+            for api in client.apis.iter_mut() {
+                for config in api.request.serialized_configs_mut() {
+                    mark_skip_empty_lists(&mut config.fields);
+                }
+            }
+        }
+
+        if client.struct_args {
+            for api in client.apis.iter_mut() {
+                if let Some(data) = &mut api.request.data {
+                    data.promote_to_struct_arg();
+                }
+            }
+        }
+
         for api in client.apis.iter_mut() {
             api.collect_and_check_vars(&client.option_map)?;
         }
 
+        // after every type comparison, which still sees the declared keywords
+        let narrow = client.narrow_ints;
+        client.visit_types_mut(&mut |typ| resolve_int_type(typ, narrow));
+
+        if let Some(case) = client.json_case.clone() {
+            client.visit_types_mut(&mut |typ| set_json_case(typ, &case));
+        }
+
         Ok(client)
     }
 }
 
 impl Client {
+    // calls `visit` on the declared type of every field and variable
+    fn visit_types_mut(&mut self, visit: &mut impl FnMut(&mut Type)) {
+        if let Some(options) = &mut self.options {
+            visit_field_types(&mut options.fields, visit);
+        }
+        for option in self.option_map.values_mut() {
+            visit_field_types(std::slice::from_mut(option), visit);
+        }
+        for api in self.apis.iter_mut() {
+            for var in api.variables.iter_mut() {
+                if let Some(typ) = &mut var.typ {
+                    visit(typ);
+                }
+            }
+            let request = &mut api.request;
+            let mut configs = vec![];
+            configs.extend(request.header.iter_mut());
+            configs.extend(request.query.iter_mut());
+            if let Some(data) = &mut request.data {
+                configs.push(&mut data.data);
+                if let Some(data_match) = &mut data.data_match {
+                    for arm in data_match.arms.iter_mut() {
+                        for var in arm.variables.iter_mut() {
+                            if let Some(typ) = &mut var.typ {
+                                visit(typ);
+                            }
+                        }
+                        configs.push(&mut arm.data);
+                    }
+                }
+            }
+            if let Some(response) = &mut api.response {
+                configs.extend(response.header.iter_mut());
+                configs.extend(response.cookie.iter_mut());
+                if let Some(data) = &mut response.data {
+                    configs.push(&mut data.data);
+                }
+            }
+            for config in configs {
+                visit_field_types(&mut config.fields, visit);
+            }
+        }
+    }
+
+    fn check_empty_structs(&self) -> syn::Result<()> {
+        if let Some(options) = &self.options {
+            options.check_not_empty()?;
+        }
+        for api in self.apis.iter() {
+            let request = &api.request;
+            let response = api.response.as_ref();
+            let arms = request
+                .data
+                .as_ref()
+                .and_then(|data| data.data_match.as_ref())
+                .map(|data_match| data_match.arms.iter().map(|arm| &arm.data));
+            for config in [
+                request
+                    .data
+                    .as_ref()
+                    .filter(|data| data.data_match.is_none())
+                    .map(|data| &data.data),
+                request.header.as_ref(),
+                request.query.as_ref(),
+                response
+                    .and_then(|r| r.data.as_ref())
+                    .map(|data| &data.data),
+                response.and_then(|r| r.header.as_ref()),
+                response.and_then(|r| r.cookie.as_ref()),
+            ]
+            .into_iter()
+            .flatten()
+            .chain(arms.into_iter().flatten())
+            {
+                config.check_not_empty()?;
+            }
+        }
+        Ok(())
+    }
+
     fn resolve_object_type_names(&mut self) -> syn::Result<()> {
         for api in self.apis.iter_mut() {
             let prefix = api.name.to_ident_with_case(Case::UpperCamel);
             if let Some(data) = &mut api.request.data {
-                data.data.resolve_types(prefix.with_suffix("RequestData"))?;
+                data.data
+                    .resolve_types(prefix.with_suffix("RequestData"), &self.type_affix)?;
+                if let Some(data_match) = &mut data.data_match {
+                    data_match.resolve_types(&prefix, &self.type_affix)?;
+                }
             };
             if let Some(headers) = &mut api.request.header {
-                headers.resolve_types(prefix.with_suffix("RequestHeaders"))?;
+                headers.resolve_types(prefix.with_suffix("RequestHeaders"), &self.type_affix)?;
             };
             if let Some(query) = &mut api.request.query {
-                query.resolve_types(prefix.with_suffix("Query"))?;
+                query.resolve_types(prefix.with_suffix("Query"), &self.type_affix)?;
             };
 
             if let Some(response) = &mut api.response {
                 if let Some(data) = &mut response.data {
                     data.data
-                        .resolve_types(prefix.with_suffix("ResponseData"))?;
+                        .resolve_types(prefix.with_suffix("ResponseData"), &self.type_affix)?;
                 }
                 if let Some(headers) = &mut response.header {
-                    headers.resolve_types(prefix.with_suffix("ResponseHeaders"))?;
+                    headers
+                        .resolve_types(prefix.with_suffix("ResponseHeaders"), &self.type_affix)?;
                 }
                 if let Some(cookies) = &mut response.cookie {
-                    cookies.resolve_types(prefix.with_suffix("ResponseCookies"))?;
+                    cookies
+                        .resolve_types(prefix.with_suffix("ResponseCookies"), &self.type_affix)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // datetime formatter modules are all emitted side by side, derived names
+    // like `foo_bar_baz_formatter` can come from `FooBar.baz` and `Foo.bar_baz`
+    fn check_formatter_names(&self) -> syn::Result<()> {
+        fn collect<'a>(fields: &'a [Field], names: &mut Vec<(&'a Ident, &'a Field)>) {
+            for field in fields {
+                match &field.typ {
+                    Some(Type::Datetime(DateTimeType {
+                        format: Some(format),
+                        ..
+                    })) => names.push((&format.mod_name, field)),
+                    Some(Type::Object(obj)) => collect(&obj.fields, names),
+                    Some(Type::JsonText(JsonStringType { typ, .. }))
+                    | Some(Type::List(ListType {
+                        element_type: typ, ..
+                    })) => {
+                        if let Type::Object(obj) = typ.as_ref() {
+                            collect(&obj.fields, names);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut names = vec![];
+        for api in self.apis.iter() {
+            let request = &api.request;
+            let mut configs = vec![];
+            configs.extend(request.header.iter());
+            configs.extend(request.query.iter());
+            if let Some(data) = &request.data {
+                configs.push(&data.data);
+                if let Some(data_match) = &data.data_match {
+                    configs.extend(data_match.arms.iter().map(|arm| &arm.data));
                 }
             }
+            if let Some(response) = &api.response {
+                configs.extend(response.data.iter().map(|data| &data.data));
+                configs.extend(response.header.iter());
+                configs.extend(response.cookie.iter());
+            }
+            for config in configs {
+                collect(&config.fields, &mut names);
+            }
+        }
+        let mut seen = HashMap::<String, &Field>::new();
+        for (mod_name, field) in names {
+            if let Some(prev) = seen.insert(mod_name.to_string(), field) {
+                let mut err = field.name.to_syn_error(format!(
+                    "datetime formatter `{mod_name}` collides with another field, \
+                     rename or alias one of them"
+                ));
+                err.combine(prev.name.to_syn_error("the other field is here"));
+                return Err(err);
+            }
         }
         Ok(())
     }
@@ -218,8 +761,9 @@ impl Hooks {
             let inner: ParseBuffer;
             let brace = syn::braced!(inner in input);
             let mut on_submit = None;
+            let mut on_response = None;
             while !inner.is_empty() {
-                if let Some(_) = inner.try_parse_comma() {
+                if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
                     continue;
                 }
 
@@ -229,13 +773,90 @@ impl Hooks {
                         token.span().to_syn_error("duplicate config").to_err()?;
                     }
                     on_submit = Some(inner.parse()?);
+                } else if let Some(token) = inner.try_parse_as_ident("on_response", false) {
+                    inner.parse::<Token![:]>()?;
+                    if on_response.is_some() {
+                        token.span().to_syn_error("duplicate config").to_err()?;
+                    }
+                    on_response = Some(inner.parse()?);
                 } else {
                     inner.span().to_syn_error("unsupported hook").to_err()?;
                 }
             }
             let span = brace.span.close();
 
-            Ok(Some(Self { span, on_submit }))
+            Ok(Some(Self {
+                span,
+                on_submit,
+                on_response,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if let Some(token) = input.try_parse_as_ident("retry", false) {
+            input.parse::<Token![:]>()?;
+            let inner: ParseBuffer;
+            let brace = syn::braced!(inner in input);
+            let mut attempts = None;
+            let mut delay = None;
+            let mut status = None;
+            while !inner.is_empty() {
+                if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
+                    continue;
+                }
+
+                if let Some(token) = inner.try_parse_as_ident("attempts", false) {
+                    inner.parse::<Token![:]>()?;
+                    if attempts.is_some() {
+                        token.span().to_syn_error("duplicate config").to_err()?;
+                    }
+                    let count = inner.parse::<LitInt>()?;
+                    if count.base10_parse::<u32>()? < 2 {
+                        count
+                            .span()
+                            .to_syn_error("expect at least 2 attempts")
+                            .to_err()?;
+                    }
+                    attempts = Some(count);
+                } else if let Some(token) = inner.try_parse_as_ident("delay", false) {
+                    inner.parse::<Token![:]>()?;
+                    if delay.is_some() {
+                        token.span().to_syn_error("duplicate config").to_err()?;
+                    }
+                    delay = Some(parse_timeout(&inner)?);
+                } else if let Some(token) = inner.try_parse_as_ident("status", false) {
+                    inner.parse::<Token![:]>()?;
+                    if status.is_some() {
+                        token.span().to_syn_error("duplicate config").to_err()?;
+                    }
+                    status = Some(parse_status_codes(&inner)?.1);
+                } else {
+                    inner
+                        .span()
+                        .to_syn_error("expect 'attempts', 'delay' or 'status'")
+                        .to_err()?;
+                }
+            }
+            let span = (token.span(), brace.span.close()).to_span();
+            let Some(attempts) = attempts else {
+                return span
+                    .to_syn_error("expect 'attempts' in retry config")
+                    .to_err();
+            };
+
+            Ok(Some(Self {
+                span,
+                attempts,
+                delay: delay.unwrap_or(LitInt::new("200u64", span)),
+                // throttled or temporarily unavailable
+                status: status
+                    .unwrap_or_else(|| vec![LitInt::new("429", span), LitInt::new("503", span)]),
+            }))
         } else {
             Ok(None)
         }
@@ -250,7 +871,7 @@ impl DataTemplates {
             syn::braced!(inner in input);
             let mut templates = DataTemplates { templates: vec![] };
             while !inner.is_empty() {
-                if let Some(_) = inner.try_parse_comma() {
+                if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
                     continue;
                 }
                 templates
@@ -313,40 +934,34 @@ impl DataTemplate {
     }
 
     fn extend(&mut self, super_template: Self) -> syn::Result<()> {
-        for field in super_template.fields.fields.into_iter().rev() {
-            if self.fields.removed_fields.contains(&field.name) {
-                continue;
-            }
-            if let Some((index, _)) = self
-                .fields
-                .fields
-                .iter()
-                .enumerate()
-                .find(|(_, f)| f.name.eq(&field.name))
-            {
-                let field = self.fields.fields.remove(index);
-                self.fields.fields.insert(0, field);
-            } else {
-                self.fields.fields.insert(0, field);
-            }
-        }
+        self.fields.extend_fields(&super_template.fields);
         Ok(())
     }
 }
 
 impl Api {
     fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
-        if let Some(method) = input.try_parse_one_of_idents(("get", "post", "put", "delete")) {
+        let blocking = input.try_parse_as_ident("blocking", false);
+        // a method is followed by the api name, unlike the `options` config
+        let method = if input.peek2(Ident) {
+            input.try_parse_one_of_idents((
+                "get", "post", "put", "patch", "delete", "head", "options",
+            ))
+        } else {
+            None
+        };
+        if let Some(method) = method {
             let name = input.parse_as_ident()?;
-            if !name.to_string().is_case(Case::Snake) {
-                name.to_syn_error("method for client expects normal snake-case name")
-                    .to_err()?;
-            }
             let url_input: ParseBuffer;
             let paren = syn::parenthesized!(url_input in input);
             let uri: ApiUri = url_input.parse()?;
 
-            let request = ApiRequest::parse(input)?;
+            // the brace may be left out when the url carries all the inputs
+            let request = if input.peek(syn::token::Brace) {
+                ApiRequest::parse(input)?
+            } else {
+                ApiRequest::empty(Brace { span: paren.span })
+            };
             let response = if input.peek(Token![->]) {
                 input.parse::<Token![->]>()?;
                 Some(input.parse()?)
@@ -362,7 +977,12 @@ impl Api {
                 request,
                 response,
                 variables: vec![],
+                blocking: blocking.map(|blocking| blocking.span()),
             }))
+        } else if let Some(blocking) = blocking {
+            blocking
+                .to_syn_error("expect an api after 'blocking'")
+                .to_err()
         } else {
             Ok(None)
         }
@@ -377,8 +997,18 @@ impl Api {
         }
         if let Some(data) = &mut self.request.data {
             data.data.extend_templates(templates)?;
+            if let Some(data_match) = &mut data.data_match {
+                for arm in data_match.arms.iter_mut() {
+                    arm.data.extend_templates(templates)?;
+                }
+            }
         }
         if let Some(response) = &mut self.response {
+            if let Some(data) = &mut response.data {
+                data.data.extend_templates(templates)?;
+                // inherited fields are held to the response rules as well
+                data.check_defaults()?;
+            }
             if let Some(header) = &mut response.header {
                 header.extend_templates(templates)?;
             }
@@ -389,13 +1019,139 @@ impl Api {
         Ok(())
     }
 
+    // `"/items?page=$page"` params join the `query` block, so one struct is
+    // sent for both; bare keys like `?raw` stay in the url
+    fn merge_uri_query(&mut self) -> syn::Result<()> {
+        let Some(uri_query) = self.uri.uri_query.take() else {
+            return Ok(());
+        };
+        let (mut params, keys): (Vec<_>, Vec<_>) = uri_query
+            .fields
+            .into_iter()
+            .partition(|field| field.expr.is_some());
+        self.uri.uri_query = (!keys.is_empty()).then(|| ApiUriQuery { fields: keys });
+        if params.is_empty() {
+            return Ok(());
+        }
+        let span = self.uri.uri_format.span();
+        let query = self.request.query.get_or_insert_with(|| BracedConfig {
+            token: span,
+            extend: None,
+            struct_name: ("Query", span).to_ident(),
+            brace: Brace(span),
+            attrs: vec![],
+            fields: vec![],
+            removed_fields: HashSet::new(),
+        });
+        for param in params.iter_mut() {
+            if let Some(prev) = query.fields.iter().find(|field| {
+                field.name.value().eq(&param.name.value()) || field.field_name.eq(&param.field_name)
+            }) {
+                (span, prev.name.span())
+                    .to_span()
+                    .to_syn_error(format!(
+                        "query param `{}` is declared in both the url and the query block",
+                        param.name.value()
+                    ))
+                    .to_err()?;
+            }
+            match &param.expr {
+                // typed by the argument, like `${page:u64}`
+                Some(Expr::Variable(var)) => {
+                    param.typ = var.typ.clone();
+                    param.optional = var.optional;
+                }
+                Some(Expr::Constant(c)) => param.default = Some(c.to_value()),
+                _ => {}
+            }
+        }
+        params.append(&mut query.fields);
+        query.fields = params;
+        Ok(())
+    }
+
+    // `DisplayName: string = $Name` takes the argument of the `Name` field,
+    // before header names are prefixed
+    fn resolve_field_refs(&mut self) {
+        let request = &mut self.request;
+        let mut configs = vec![];
+        configs.extend(request.header.iter_mut());
+        configs.extend(request.query.iter_mut());
+        if let Some(data) = &mut request.data {
+            configs.push(&mut data.data);
+            if let Some(data_match) = &mut data.data_match {
+                configs.extend(data_match.arms.iter_mut().map(|arm| &mut arm.data));
+            }
+        }
+        for config in configs {
+            resolve_field_refs(&mut config.fields);
+        }
+    }
+
     fn collect_and_check_vars(&mut self, options: &HashMap<Ident, Field>) -> syn::Result<()> {
+        for config in self.request.configs() {
+            config.reject_response_only()?;
+            let multipart = self.request.data.as_ref().is_some_and(|data| {
+                matches!(data.data_type, DataType::Multipart(_)) && std::ptr::eq(&data.data, config)
+            });
+            config.check_file_fields(multipart)?;
+        }
+        if let Some(response) = &self.response {
+            for config in [response.header.as_ref(), response.cookie.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                config.reject_response_only()?;
+                config.check_file_fields(false)?;
+            }
+            if let Some(data) = &response.data {
+                data.data.check_file_fields(false)?;
+            }
+        }
+        self.uri.check_client_options(options)?;
         self.uri.collect_vars(&mut self.variables)?;
         self.request.collect_vars(&mut self.variables)?;
-
-        for var in self.variables.iter_mut() {
-            if var.client_option {
-                if let Some(opt) = options.get(&var.name) {
+        let required = self
+            .variables
+            .iter()
+            .filter(|var| var.optional.is_none())
+            .map(|var| var.name.clone())
+            .collect::<HashSet<_>>();
+        self.request.mark_required_vars(&required);
+        if let Some(data_match) = self
+            .request
+            .data
+            .as_mut()
+            .and_then(|data| data.data_match.as_mut())
+        {
+            data_match.collect_and_check_vars(&self.variables)?;
+        }
+        if let Some(BytesBody { var, .. }) = &self.request.bytes_body {
+            if self.variables.iter().any(|other| other.name.eq(var)) {
+                var.to_syn_error("conflicts with another argument")
+                    .to_err()?;
+            }
+        }
+        if let Some(key) = &self.request.key {
+            for name in key.names.iter() {
+                if !self.variables.iter().any(|var| var.name.eq(name)) {
+                    name.to_syn_error("no such variable in this api").to_err()?;
+                }
+            }
+        }
+        self.check_page_stream()?;
+
+        for config in self.request.configs() {
+            config.check_conditions(options)?;
+        }
+        self.request.mark_shared_vars();
+        if let Some(header) = &mut self.request.header {
+            header.check_header_values(&self.variables, options)?;
+        }
+
+        for var in self.variables.iter_mut() {
+            if var.client_option {
+                if let Some(opt) = options.get(&var.name) {
                     if let Some(typ) = &var.typ {
                         if let Some(opt_type) = &opt.typ {
                             if opt_type.ne(&typ) {
@@ -415,6 +1171,54 @@ impl Api {
         }
         Ok(())
     }
+
+    // every page is requested with the same arguments but the page number,
+    // so they are cloned, which the generated structs don't support
+    fn check_page_stream(&self) -> syn::Result<()> {
+        let Some(PageStream { next, .. }) = self
+            .response
+            .as_ref()
+            .and_then(|response| response.page_info.as_ref())
+            .and_then(|page_info| page_info.stream.as_ref())
+        else {
+            return Ok(());
+        };
+        match self.variables.iter().find(|var| var.name.eq(next)) {
+            Some(var) if var.optional.is_none() && matches!(var.typ, Some(Type::Integer(_))) => {}
+            Some(_) => next
+                .to_syn_error("expect a required integer argument")
+                .to_err()?,
+            None => next.to_syn_error("no such variable in this api").to_err()?,
+        }
+        fn has_object(typ: &Type) -> bool {
+            match typ {
+                Type::Object(_) => true,
+                Type::List(ListType { element_type, .. }) => has_object(element_type),
+                _ => false,
+            }
+        }
+        let request = &self.request;
+        let struct_arg = request
+            .data
+            .as_ref()
+            .and_then(|data| {
+                data.data_var
+                    .as_ref()
+                    .or(data.data_match.as_ref().map(|m| &m.var))
+            })
+            .or(request.query.as_ref().and(request.query_var.as_ref()))
+            .or(request.header.as_ref().and(request.header_var.as_ref()))
+            .or(self
+                .variables
+                .iter()
+                .find(|var| var.typ.as_ref().is_some_and(has_object))
+                .map(|var| &var.name));
+        if let Some(arg) = struct_arg {
+            arg.to_syn_error("an item stream cannot clone this struct argument")
+                .to_err()?;
+        }
+        Ok(())
+    }
 }
 
 impl Parse for ApiUri {
@@ -440,9 +1244,6 @@ impl Parse for ApiUri {
 
 impl ApiUri {
     fn collect_vars(&self, variables: &mut Vec<Variable>) -> syn::Result<()> {
-        for var in self.uri_variables.iter() {
-            variables.collect(var, None)?;
-        }
         if let Some(var) = &self.port_var {
             variables.collect(
                 var,
@@ -452,12 +1253,14 @@ impl ApiUri {
                 })),
             )?;
         }
-        if let Some(path) = &self.uri_path {
-            for seg in path.segments.iter() {
-                if let ApiUriSeg::Var(var) = seg {
-                    variables.collect(var, None)?;
+        // host and path variables, the port variable is collected above
+        for var in self.uri_variables.iter() {
+            if let Some(port_var) = &self.port_var {
+                if port_var.name.eq(&var.name) {
+                    continue;
                 }
             }
+            variables.collect(var, None)?;
         }
         if let Some(query) = &self.uri_query {
             for field in query.fields.iter() {
@@ -468,23 +1271,43 @@ impl ApiUri {
         }
         Ok(())
     }
+
+    // `$$host` and `$$port` read client options, which are not collected as
+    // arguments
+    fn check_client_options(&self, options: &HashMap<Ident, Field>) -> syn::Result<()> {
+        let query_vars = self.uri_query.iter().flat_map(|query| {
+            query.fields.iter().filter_map(|field| match &field.expr {
+                Some(Expr::Variable(var)) => Some(var),
+                _ => None,
+            })
+        });
+        for var in self.uri_variables.iter().chain(query_vars) {
+            if var.client_option && !options.contains_key(&var.name) {
+                var.name.to_syn_error("no such option").to_err()?;
+            }
+        }
+        if let Some(var) = self.port_var.as_ref().filter(|var| var.client_option) {
+            if !matches!(
+                options.get(&var.name).and_then(|opt| opt.typ.as_ref()),
+                Some(Type::Integer(_))
+            ) {
+                var.name
+                    .to_syn_error("port option expects an integer type, like `u16`")
+                    .to_err()?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ApiRequest {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let inner: ParseBuffer;
         let brace = syn::braced!(inner in input);
-        let mut request = Self {
-            brace,
-            header: None,
-            query: None,
-            data: None,
-            header_var: None,
-            query_var: None,
-        };
+        let mut request = Self::empty(brace);
 
         while !inner.is_empty() {
-            if let Some(_comma) = inner.try_parse_comma() {
+            if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
                 continue;
             }
 
@@ -495,7 +1318,72 @@ impl ApiRequest {
                         .to_syn_error("duplicated request body config")
                         .to_err()?;
                 }
+                if let Some(prev) = &request.bytes_body {
+                    (data.data.token, prev.token)
+                        .to_span()
+                        .to_syn_error("duplicated request body config")
+                        .to_err()?;
+                }
                 request.data = Some(data);
+            } else if let Some(token) = inner.try_parse_as_ident("bytes", false) {
+                if let Some(prev) = request
+                    .data
+                    .as_ref()
+                    .map(|data| data.data.token)
+                    .or(request.bytes_body.as_ref().map(|body| body.token))
+                {
+                    (token.span(), prev)
+                        .to_span()
+                        .to_syn_error("duplicated request body config")
+                        .to_err()?;
+                }
+                let Some(var) = Self::parse_var_part(&inner)? else {
+                    return token.to_syn_error("expect `bytes = $var`").to_err();
+                };
+                request.bytes_body = Some(BytesBody {
+                    token: token.span(),
+                    var,
+                });
+            } else if let Some(accept) = inner.try_parse_as_ident("accept", false) {
+                if let Some(prev) = &request.accept {
+                    (accept.span(), prev.span())
+                        .to_span()
+                        .to_syn_error("duplicated accept config")
+                        .to_err()?;
+                }
+                inner.parse::<Token![:]>()?;
+                let content_type = inner.parse::<LitStr>()?;
+                if content_type.value().trim().is_empty() {
+                    content_type
+                        .to_syn_error("expect a content type")
+                        .to_err()?;
+                }
+                request.accept = Some(content_type);
+            } else if let Some(transfer) = inner.try_parse_as_ident("transfer", false) {
+                if let Some(prev) = &request.transfer {
+                    (transfer.span(), prev.span())
+                        .to_span()
+                        .to_syn_error("duplicated transfer config")
+                        .to_err()?;
+                }
+                inner.parse::<Token![:]>()?;
+                request.transfer = Some(Transfer::parse(&inner)?);
+            } else if let Some(key) = RequestKey::try_parse(&inner)? {
+                if let Some(prev) = &request.key {
+                    (key.span, prev.span)
+                        .to_span()
+                        .to_syn_error("duplicated key config")
+                        .to_err()?;
+                }
+                request.key = Some(key);
+            } else if let Some(auth) = BasicAuth::try_parse(&inner)? {
+                if let Some(prev) = &request.auth {
+                    (auth.span, prev.span)
+                        .to_span()
+                        .to_syn_error("duplicated auth config")
+                        .to_err()?;
+                }
+                request.auth = Some(auth);
             } else if let Some(query) = inner.try_parse_as_ident("query", false) {
                 if let Some(prev) = request.query {
                     (query.span(), prev.token)
@@ -504,12 +1392,13 @@ impl ApiRequest {
                         .to_err()?;
                 }
                 let extend = BracedConfig::peek_and_parse_extend(&inner)?;
+                // `Name -> name` renames the field while keeping the query key
                 request.query = Some(BracedConfig::parse(
                     &inner,
                     query.span(),
                     extend,
                     true,
-                    false,
+                    true,
                     true,
                 )?);
                 request.query_var = Self::parse_var_part(&inner)?;
@@ -538,8 +1427,34 @@ impl ApiRequest {
             }
         }
 
+        // serialized bodies are framed by reqwest
+        if request.bytes_body.is_none() {
+            if let Some(transfer) = &request.transfer {
+                transfer
+                    .span()
+                    .to_syn_error("transfer config requires a `bytes = $var` body")
+                    .to_err()?;
+            }
+        }
+
         Ok(request)
     }
+    fn empty(brace: Brace) -> Self {
+        Self {
+            brace,
+            header: None,
+            query: None,
+            data: None,
+            header_var: None,
+            query_var: None,
+            auth: None,
+            accept: None,
+            transfer: None,
+            key: None,
+            bytes_body: None,
+        }
+    }
+
     fn parse_var_part(input: ParseStream) -> syn::Result<Option<Ident>> {
         Ok(if let Some(_) = input.try_parse_eq() {
             input.parse::<Token![$]>()?;
@@ -556,32 +1471,501 @@ impl ApiRequest {
         if let Some(query) = &self.query {
             query.collect_vars(vars, &self.query_var)?;
         }
-        if let Some(data) = &self.data {
-            data.data.collect_vars(vars, &data.data_var)?;
+        if let Some(data) = &self.data {
+            data.data.collect_vars(vars, &data.data_var)?;
+        }
+        if let Some(auth) = &self.auth {
+            for expr in [Some(&auth.user), auth.passwd.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                expr.collect_vars(
+                    vars,
+                    Some(&Type::String(StringType {
+                        span: expr.to_span(),
+                    })),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ApiRequest {
+    // the query and the body configs, with the data of every match arm
+    fn serialized_configs_mut(&mut self) -> Vec<&mut BracedConfig> {
+        let mut configs = self.query.iter_mut().collect::<Vec<_>>();
+        if let Some(data) = &mut self.data {
+            configs.push(&mut data.data);
+            if let Some(data_match) = &mut data.data_match {
+                configs.extend(data_match.arms.iter_mut().map(|arm| &mut arm.data));
+            }
+        }
+        configs
+    }
+
+    // an argument used more than once must be cloned where it is moved
+    fn mark_shared_vars(&mut self) {
+        let mut usages = vec![];
+        for config in [self.header.as_mut(), self.query.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            collect_field_vars_mut(&mut config.fields, &mut usages);
+        }
+        if let Some(data) = &mut self.data {
+            collect_field_vars_mut(&mut data.data.fields, &mut usages);
+            if let Some(data_match) = &mut data.data_match {
+                for arm in data_match.arms.iter_mut() {
+                    collect_field_vars_mut(&mut arm.data.fields, &mut usages);
+                }
+            }
+        }
+        if let Some(auth) = &mut self.auth {
+            usages.extend(auth.user.variables_mut());
+            if let Some(passwd) = &mut auth.passwd {
+                usages.extend(passwd.variables_mut());
+            }
+        }
+        usages.retain(|(var, _)| !var.client_option);
+        let mut counts = HashMap::<Ident, usize>::new();
+        for (var, _) in usages.iter() {
+            *counts.entry(var.name.clone()).or_default() += 1;
+        }
+        for (var, moved) in usages {
+            var.shared = moved && counts[&var.name] > 1;
+        }
+    }
+
+    fn mark_required_vars(&mut self, required: &HashSet<Ident>) {
+        for config in [
+            self.header.as_mut(),
+            self.query.as_mut(),
+            self.data.as_mut().map(|data| &mut data.data),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            config.mark_required_vars(required);
+        }
+    }
+}
+
+// standard headers keep their names under `header_prefix`
+const WELL_KNOWN_HEADERS: &[&str] = &[
+    "accept",
+    "accept-charset",
+    "accept-encoding",
+    "accept-language",
+    "authorization",
+    "cache-control",
+    "connection",
+    "content-disposition",
+    "content-encoding",
+    "content-language",
+    "content-length",
+    "content-md5",
+    "content-type",
+    "cookie",
+    "date",
+    "expect",
+    "host",
+    "if-match",
+    "if-modified-since",
+    "if-none-match",
+    "if-unmodified-since",
+    "origin",
+    "pragma",
+    "range",
+    "referer",
+    "user-agent",
+];
+
+impl BracedConfig {
+    fn prefix_header_names(&mut self, prefix: &LitStr) {
+        let prefix = prefix.value();
+        for field in self.fields.iter_mut() {
+            let name = field.name.value();
+            let lowercased = name.to_lowercase();
+            if WELL_KNOWN_HEADERS.contains(&lowercased.as_str())
+                || lowercased.starts_with(&prefix.to_lowercase())
+            {
+                continue;
+            }
+            field.name = LitStr::new(&format!("{prefix}{name}"), field.name.span());
+        }
+    }
+
+    fn reject_response_only(&self) -> syn::Result<()> {
+        if let Some(path) = self
+            .fields
+            .iter()
+            .find_map(|field| field.json_path.as_ref())
+        {
+            path.span
+                .to_syn_error("json path is only for response data fields")
+                .to_err()?;
+        }
+        if let Some(require) = self.fields.iter().find_map(|field| field.require) {
+            require
+                .to_syn_error("require is only for response data fields")
+                .to_err()?;
+        }
+        Ok(())
+    }
+
+    // files are top-level parts of a multipart body, or lists of them
+    fn check_file_fields(&self, multipart: bool) -> syn::Result<()> {
+        for typ in self.fields.iter().filter_map(|field| field.typ.as_ref()) {
+            match typ {
+                Type::File(_) if multipart => {}
+                Type::List(ListType { element_type, .. })
+                    if multipart && matches!(element_type.as_ref(), Type::File(_)) => {}
+                typ => reject_file_type(typ)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn check_conditions(&self, options: &HashMap<Ident, Field>) -> syn::Result<()> {
+        for var in self
+            .fields
+            .iter()
+            .filter_map(|field| field.condition.as_ref())
+        {
+            match options.get(&var.name).map(|opt| &opt.typ) {
+                Some(Some(Type::Bool(_))) => {}
+                Some(_) => var
+                    .to_span()
+                    .to_syn_error("condition requires a bool client option")
+                    .to_err()?,
+                None => var.name.to_syn_error("no such option").to_err()?,
+            }
+        }
+        Ok(())
+    }
+
+    fn mark_required_vars(&mut self, required: &HashSet<Ident>) {
+        for field in self.fields.iter_mut() {
+            if let Some(Expr::Variable(var) | Expr::Or(OrExpr { variable: var, .. })) =
+                field.expr.as_mut()
+            {
+                if required.contains(&var.name) {
+                    var.optional = None;
+                }
+            }
+        }
+    }
+}
+
+impl BasicAuth {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if let Some(auth) = input.try_parse_as_ident("auth", false) {
+            input.parse::<Token![:]>()?;
+            let scheme = input.parse::<Ident>()?;
+            if !scheme.eq("basic") {
+                scheme
+                    .to_syn_error("only 'basic' auth is supported")
+                    .to_err()?;
+            }
+            let inner: ParseBuffer;
+            let paren = syn::parenthesized!(inner in input);
+            let mut args = inner
+                .parse_terminated(Expr::parse, Token![,])?
+                .into_iter()
+                .collect::<Vec<_>>();
+            if args.is_empty() || args.len() > 2 {
+                paren
+                    .span
+                    .join()
+                    .to_syn_error("expect basic(user) or basic(user, password)")
+                    .to_err()?;
+            }
+            for arg in args.iter_mut() {
+                arg.resolve_optional_var(None)?;
+            }
+            let passwd = if args.len() == 2 { args.pop() } else { None };
+            let user = args.pop().unwrap();
+            Ok(Some(Self {
+                span: (auth.span(), paren.span.close()).to_span(),
+                user,
+                passwd,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl DataMatch {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if !input.peek(Token![match]) {
+            return Ok(None);
+        }
+        let token = input.parse::<Token![match]>()?.span;
+        input.parse::<Token![$]>()?;
+        let var: Ident = input.parse()?;
+        let inner: ParseBuffer;
+        let brace = syn::braced!(inner in input);
+        let mut arms: Vec<DataArm> = vec![];
+        while !inner.is_empty() {
+            if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
+                continue;
+            }
+            let name = inner.parse::<Ident>()?.to_ident_with_case(Case::UpperCamel);
+            if let Some(prev) = arms.iter().find(|arm| arm.name.eq(&name)) {
+                (name.span(), prev.name.span())
+                    .to_span()
+                    .to_syn_error("duplicated match arm")
+                    .to_err()?;
+            }
+            inner.parse::<Token![=>]>()?;
+            let extend = BracedConfig::peek_and_parse_extend(&inner)?;
+            let data = BracedConfig::parse(&inner, name.span(), extend, true, true, true)?;
+            arms.push(DataArm {
+                name,
+                data,
+                variables: vec![],
+            });
+        }
+        if arms.is_empty() {
+            brace
+                .span
+                .join()
+                .to_syn_error("expect at least one match arm")
+                .to_err()?;
+        }
+        Ok(Some(Self {
+            token,
+            enum_name: var.to_ident_with_case(Case::UpperCamel),
+            var,
+            brace,
+            arms,
+        }))
+    }
+
+    fn resolve_types(&mut self, prefix: &Ident, affix: &TypeAffix) -> syn::Result<()> {
+        self.enum_name = affix
+            .apply(prefix.with_suffix(self.var.to_ident_with_case(Case::UpperCamel).to_string()));
+        for arm in self.arms.iter_mut() {
+            arm.data.resolve_types(
+                prefix.with_suffix(format!("RequestData{}", arm.name)),
+                affix,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn collect_and_check_vars(&mut self, api_vars: &[Variable]) -> syn::Result<()> {
+        if let Some(prev) = api_vars.iter().find(|var| var.name.eq(&self.var)) {
+            (self.var.span(), prev.name.span())
+                .to_span()
+                .to_syn_error("match variable is already used as an argument")
+                .to_err()?;
+        }
+        for arm in self.arms.iter_mut() {
+            arm.data.collect_vars(&mut arm.variables, &None)?;
+            for var in arm.variables.iter() {
+                if var.name.eq(&self.var) {
+                    var.name
+                        .to_syn_error("match variable cannot be used inside its arms")
+                        .to_err()?;
+                }
+                if let Some(prev) = api_vars.iter().find(|prev| prev.name.eq(&var.name)) {
+                    (var.name.span(), prev.name.span())
+                        .to_span()
+                        .to_syn_error("variable in match arm is already an argument of the api")
+                        .to_err()?;
+                }
+            }
+            let required = arm
+                .variables
+                .iter()
+                .filter(|var| var.optional.is_none())
+                .map(|var| var.name.clone())
+                .collect::<HashSet<_>>();
+            arm.data.mark_required_vars(&required);
+        }
+        Ok(())
+    }
+}
+
+impl ApiRequestData {
+    // the caller passes the whole data struct, so plain variable fields come
+    // from it rather than from separate arguments
+    fn promote_to_struct_arg(&mut self) {
+        if self.data_var.is_some() || self.data_match.is_some() {
+            return;
+        }
+        self.data_var = Some(("data", self.data.token).to_ident());
+        for field in self.data.fields.iter_mut() {
+            if let Some(Expr::Variable(var) | Expr::Or(OrExpr { variable: var, .. })) = &field.expr
+            {
+                if !var.client_option {
+                    field.expr = None;
+                }
+            }
+        }
+    }
+
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if let Some(ident) = input.try_parse_one_of_idents((
+            "json",
+            "form",
+            "urlencoded",
+            "urlencode",
+            "urlenc",
+            "multipart",
+        )) {
+            let data_type = match ident.to_string().as_str() {
+                "json" => DataType::Json(ident.span()),
+                "form" => DataType::Form(ident.span()),
+                "urlencoded" | "urlencode" | "urlenc" => DataType::Urlencoded(ident.span()),
+                "multipart" => DataType::Multipart(ident.span()),
+                _ => {
+                    unreachable!()
+                }
+            };
+            let optional = input.try_parse_question().map(|q| q.span());
+            let encoding = UrlEncoding::try_parse(input)?;
+            if let Some(encoding) = &encoding {
+                if !matches!(data_type, DataType::Urlencoded(_)) {
+                    encoding
+                        .span()
+                        .to_syn_error("encoding is only supported by urlencoded body")
+                        .to_err()?;
+                }
+            }
+            if let Some(data_match) = DataMatch::try_parse(input)? {
+                if let DataType::Form(span) | DataType::Multipart(span) = &data_type {
+                    (*span, data_match.token)
+                        .to_span()
+                        .to_syn_error(format!("match is not supported by {ident} body"))
+                        .to_err()?;
+                }
+                if let Some(optional) = optional {
+                    (optional, data_match.token)
+                        .to_span()
+                        .to_syn_error("a matched body cannot be optional")
+                        .to_err()?;
+                }
+                let data = BracedConfig {
+                    token: ident.span(),
+                    extend: None,
+                    struct_name: ("_", ident.span()).to_ident(),
+                    brace: data_match.brace,
+                    attrs: vec![],
+                    fields: vec![],
+                    removed_fields: HashSet::new(),
+                };
+                return Ok(Some(Self {
+                    data_type,
+                    encoding,
+                    data,
+                    data_var: None,
+                    optional: None,
+                    array: None,
+                    data_match: Some(data_match),
+                }));
+            }
+            let extend = BracedConfig::peek_and_parse_extend(input)?;
+            let (data, array) = if input.peek(Bracket) {
+                if !matches!(data_type, DataType::Json(_)) {
+                    ident
+                        .to_syn_error("array body is only supported by json")
+                        .to_err()?;
+                }
+                let inner: ParseBuffer;
+                let bracket = syn::bracketed!(inner in input);
+                let data = BracedConfig::parse(&inner, ident.span(), extend, true, true, true)?;
+                if !inner.is_empty() {
+                    inner
+                        .span()
+                        .to_syn_error("expect one object type in array body")
+                        .to_err()?;
+                }
+                (data, Some(bracket))
+            } else {
+                let data = BracedConfig::parse(input, ident.span(), extend, true, true, true)?;
+                (data, None)
+            };
+            let data_var = ApiRequest::parse_var_part(input)?;
+            let mut data = Self {
+                data_type,
+                encoding,
+                data,
+                data_var,
+                optional,
+                array,
+                data_match: None,
+            };
+            // there are no separate arguments to leave out, the caller passes
+            // the whole data or nothing
+            if data.optional.is_some() || data.array.is_some() {
+                data.promote_to_struct_arg();
+            }
+            Ok(Some(data))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl UrlEncoding {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if input.peek(syn::token::Paren) {
+            let inner: ParseBuffer;
+            syn::parenthesized!(inner in input);
+            if let Some(ident) = inner.try_parse_one_of_idents(("rfc3986", "percent")) {
+                Ok(Some(Self::Rfc3986(ident.span())))
+            } else if let Some(ident) = inner.try_parse_one_of_idents(("form", "www_form")) {
+                Ok(Some(Self::Form(ident.span())))
+            } else {
+                inner
+                    .span()
+                    .to_syn_error("unsupported encoding, expect 'rfc3986' or 'form'")
+                    .to_err()
+            }
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Form(span) => *span,
+            Self::Rfc3986(span) => *span,
         }
-        Ok(())
     }
 }
 
-impl ApiRequestData {
+impl RequestKey {
     fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
-        if let Some(ident) =
-            input.try_parse_one_of_idents(("json", "form", "urlencoded", "urlencode", "urlenc"))
-        {
-            let extend = BracedConfig::peek_and_parse_extend(input)?;
-            let data = BracedConfig::parse(input, ident.span(), extend, true, true, true)?;
-            let data_var = ApiRequest::parse_var_part(input)?;
+        if let Some(key) = input.try_parse_as_ident("key", false) {
+            input.parse::<Token![:]>()?;
+            let inner: ParseBuffer;
+            let bracket = syn::bracketed!(inner in input);
+            let mut names: Vec<Ident> = vec![];
+            for name in inner.parse_terminated(Ident::parse, Token![,])? {
+                if let Some(prev) = names.iter().find(|prev| prev.eq(&&name)) {
+                    (name.span(), prev.span())
+                        .to_span()
+                        .to_syn_error("duplicated key field")
+                        .to_err()?;
+                }
+                names.push(name);
+            }
+            if names.is_empty() {
+                bracket
+                    .span
+                    .join()
+                    .to_syn_error("expect at least one key field")
+                    .to_err()?;
+            }
             Ok(Some(Self {
-                data_type: match ident.to_string().as_str() {
-                    "json" => DataType::Json(ident.span()),
-                    "form" => DataType::Form(ident.span()),
-                    "urlencoded" | "urlencode" | "urlenc" => DataType::Urlencoded(ident.span()),
-                    _ => {
-                        unreachable!()
-                    }
-                },
-                data,
-                data_var,
+                span: (key.span(), bracket.span.close()).to_span(),
+                bracket,
+                names,
             }))
         } else {
             Ok(None)
@@ -589,6 +1973,28 @@ impl ApiRequestData {
     }
 }
 
+impl Transfer {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if let Some(ident) = input.try_parse_as_ident("chunked", false) {
+            Ok(Self::Chunked(ident.span()))
+        } else if let Some(ident) = input.try_parse_as_ident("length", false) {
+            Ok(Self::Length(ident.span()))
+        } else {
+            input
+                .span()
+                .to_syn_error("unsupported transfer, expect 'chunked' or 'length'")
+                .to_err()
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Chunked(span) => *span,
+            Self::Length(span) => *span,
+        }
+    }
+}
+
 trait VariableCollector {
     fn collect(&mut self, var: &Variable, suggested_type: Option<&Type>) -> syn::Result<()>;
 }
@@ -598,37 +2004,42 @@ impl VariableCollector for Vec<Variable> {
         if var.client_option {
             return Ok(());
         }
-        if let Some(old) = self.iter().find(|old| old.name.eq(&var.name)) {
-            if let Some(old_type) = &old.typ {
-                if let Some(typ) = suggested_type {
-                    // compare type
-                    if typ.ne(old_type) {
-                        (typ.to_span(), old_type.to_span())
-                            .to_span()
-                            .to_syn_error("unmatched variable type")
-                            .to_err()?;
-                    }
-                } else if !old_type.is_string() {
-                    // check old type is string
-                    old_type
-                        .to_span()
-                        .to_syn_error("expect string type for the variable")
-                        .to_err()?;
+        // type declared with the variable itself (e.g. `${id:u64}`) takes precedence
+        let suggested_type = var.typ.as_ref().or(suggested_type);
+        if let Some(old) = self.iter_mut().find(|old| old.name.eq(&var.name)) {
+            // an untyped usage, like a url segment, takes any type; the most
+            // specific one wins, untyped variables end up as strings
+            let (Some(old_type), Some(typ)) = (old.typ.as_ref(), suggested_type) else {
+                if old.typ.is_none() {
+                    old.typ = suggested_type.map(|t| t.pure());
                 }
-            } else if let Some(typ) = suggested_type {
-                // check new type whether is string
-                if !typ.is_string() {
-                    (typ.to_span(), old.name.span())
-                        .to_span()
-                        .to_syn_error("expect string type (unmatched with previous variable type)")
-                        .to_err()?;
+                // a variable is optional only if all of its usages are optional
+                if var.optional.is_none() {
+                    old.optional = None;
                 }
+                return Ok(());
+            };
+            if typ.ne(old_type) {
+                let mut err = typ.to_span().to_syn_error(format!(
+                    "conflicting types for variable `{}`: used as {} here",
+                    var.name,
+                    typ.describe()
+                ));
+                err.combine(
+                    old_type
+                        .to_span()
+                        .to_syn_error(format!("but used as {} here", old_type.describe())),
+                );
+                return Err(err);
             }
+            // a variable is optional only if all of its usages are optional
+            if var.optional.is_none() {
+                old.optional = None;
+            }
+            return Ok(());
         }
         let mut var = var.clone();
-        if var.typ.is_none() {
-            var.typ = suggested_type.map(|t| t.pure())
-        }
+        var.typ = suggested_type.map(|t| t.pure());
         self.push(var);
         Ok(())
     }
@@ -636,17 +2047,23 @@ impl VariableCollector for Vec<Variable> {
 
 impl Parse for ApiResponse {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let optional = input.try_parse_question().map(|q| q.span());
         let inner: ParseBuffer;
         let brace = syn::braced!(inner in input);
         let mut response = Self {
+            optional,
             brace,
             header: None,
             cookie: None,
             data: None,
+            text: None,
+            page_info: None,
+            success_status: None,
+            rate_limit_info: None,
         };
 
         while !inner.is_empty() {
-            if let Some(_comma) = inner.try_parse_comma() {
+            if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
                 continue;
             }
 
@@ -657,7 +2074,51 @@ impl Parse for ApiResponse {
                         .to_syn_error("duplicated json config")
                         .to_err()?;
                 }
+                if let Some(text) = response.text {
+                    (data.data.token, text)
+                        .to_span()
+                        .to_syn_error("text response cannot be combined with a data body")
+                        .to_err()?;
+                }
                 response.data = Some(data);
+            } else if let Some(success_status) = SuccessStatus::try_parse(&inner)? {
+                if let Some(prev) = &response.success_status {
+                    (success_status.span, prev.span)
+                        .to_span()
+                        .to_syn_error("duplicated success_status config")
+                        .to_err()?;
+                }
+                response.success_status = Some(success_status);
+            } else if let Some(page_info) = PageInfo::try_parse(&inner)? {
+                if let Some(prev) = &response.page_info {
+                    (page_info.span, prev.span)
+                        .to_span()
+                        .to_syn_error("duplicated page_info config")
+                        .to_err()?;
+                }
+                response.page_info = Some(page_info);
+            } else if let Some(token) = inner.try_parse_as_ident("rate_limit_info", false) {
+                if let Some(prev) = response.rate_limit_info {
+                    (token.span(), prev)
+                        .to_span()
+                        .to_syn_error("duplicated rate_limit_info config")
+                        .to_err()?;
+                }
+                response.rate_limit_info = Some(token.span());
+            } else if let Some(text) = inner.try_parse_as_ident("text", false) {
+                if let Some(prev) = response.text {
+                    (text.span(), prev)
+                        .to_span()
+                        .to_syn_error("duplicated text config")
+                        .to_err()?;
+                }
+                if let Some(data) = &response.data {
+                    (text.span(), data.data.token)
+                        .to_span()
+                        .to_syn_error("text response cannot be combined with a data body")
+                        .to_err()?;
+                }
+                response.text = Some(text.span());
             } else if let Some(cookie) = inner.try_parse_as_ident("cookie", false) {
                 if let Some(prev) = &response.cookie {
                     (cookie.span(), prev.token)
@@ -698,9 +2159,178 @@ impl Parse for ApiResponse {
             }
         }
 
+        if let Some(page_info) = &mut response.page_info {
+            page_info.resolve(response.data.as_ref())?;
+        }
+
         Ok(response)
     }
 }
+impl SuccessStatus {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if let Some(token) = input.try_parse_as_ident("success_status", false) {
+            input.parse::<Token![:]>()?;
+            let (bracket, codes) = parse_status_codes(input)?;
+            Ok(Some(Self {
+                span: (token.span(), bracket.span.close()).to_span(),
+                bracket,
+                codes,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+fn reject_file_type(typ: &Type) -> syn::Result<()> {
+    match typ {
+        Type::File(file) => file
+            .span
+            .to_syn_error("file is only supported by multipart body fields")
+            .to_err(),
+        Type::Object(obj) => {
+            for typ in obj.fields.iter().filter_map(|field| field.typ.as_ref()) {
+                reject_file_type(typ)?;
+            }
+            Ok(())
+        }
+        Type::List(ListType { element_type, .. }) => reject_file_type(element_type),
+        Type::JsonText(JsonStringType { typ, .. }) => reject_file_type(typ),
+        _ => Ok(()),
+    }
+}
+
+// `[200, 202]`
+fn parse_status_codes(input: ParseStream) -> syn::Result<(Bracket, Vec<LitInt>)> {
+    let inner: ParseBuffer;
+    let bracket = syn::bracketed!(inner in input);
+    let mut codes: Vec<LitInt> = vec![];
+    for code in inner.parse_terminated(|input| input.parse::<LitInt>(), Token![,])? {
+        match code.base10_parse::<u16>() {
+            Ok(100..=599) => {}
+            _ => code
+                .span()
+                .to_syn_error("expect a http status code in 100..=599")
+                .to_err()?,
+        }
+        if codes
+            .iter()
+            .any(|prev| prev.base10_digits().eq(code.base10_digits()))
+        {
+            code.span()
+                .to_syn_error("duplicated status code")
+                .to_err()?;
+        }
+        codes.push(code);
+    }
+    if codes.is_empty() {
+        bracket
+            .span
+            .join()
+            .to_syn_error("expect at least one status code")
+            .to_err()?;
+    }
+    Ok((bracket, codes))
+}
+
+impl PageInfo {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if let Some(token) = input.try_parse_as_ident("page_info", false) {
+            let inner: ParseBuffer;
+            let brace = syn::braced!(inner in input);
+            let (mut total, mut page, mut size) = (None, None, None);
+            let (mut items, mut next) = (None, None);
+            while !inner.is_empty() {
+                if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
+                    continue;
+                }
+                let key = inner.parse::<Ident>()?;
+                inner.parse::<Token![:]>()?;
+                let slot = match key.to_string().as_str() {
+                    "total" => &mut total,
+                    "page" => &mut page,
+                    "size" => &mut size,
+                    "items" => &mut items,
+                    "next" => {
+                        inner.parse::<Token![$]>()?;
+                        &mut next
+                    }
+                    _ => key
+                        .to_syn_error("expect 'total', 'page', 'size', 'items' or 'next'")
+                        .to_err()?,
+                };
+                let field = inner.parse::<Ident>()?;
+                if slot.is_some() {
+                    key.to_syn_error(format!("duplicated {key}")).to_err()?;
+                }
+                *slot = Some(field);
+            }
+            let span = (token.span(), brace.span.close()).to_span();
+            let missing = |name: &str| {
+                brace
+                    .span
+                    .join()
+                    .to_syn_error(format!("missing '{name}' in page_info"))
+            };
+            let stream = match (items, next) {
+                (Some(items), Some(next)) => Some(PageStream { items, next }),
+                (None, None) => None,
+                (Some(_), None) => Err(missing("next"))?,
+                (None, Some(_)) => Err(missing("items"))?,
+            };
+            Ok(Some(Self {
+                span,
+                total: total.ok_or_else(|| missing("total"))?,
+                page: page.ok_or_else(|| missing("page"))?,
+                size: size.ok_or_else(|| missing("size"))?,
+                stream,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // the named fields must be integers in the response data, referred by
+    // either the declared name or the field name
+    fn resolve(&mut self, data: Option<&ApiResponseData>) -> syn::Result<()> {
+        let Some(data) = data else {
+            return self
+                .span
+                .to_syn_error("page_info requires a json or form response body")
+                .to_err();
+        };
+        for name in [&mut self.total, &mut self.page, &mut self.size] {
+            let field = data.data.fields.iter().find(|field| {
+                field.name.value().eq(&name.to_string()) || field.field_name.eq(name)
+            });
+            match field {
+                Some(field) if matches!(field.typ, Some(Type::Integer(_))) => {
+                    *name = field.field_name.clone();
+                }
+                Some(_) => name.to_syn_error("expect an integer field").to_err()?,
+                None => name
+                    .to_syn_error("no such field in response data")
+                    .to_err()?,
+            }
+        }
+        if let Some(PageStream { items, .. }) = &mut self.stream {
+            let field = data.data.fields.iter().find(|field| {
+                field.field_name.eq(items) || field.name.value().eq(&items.to_string())
+            });
+            match field {
+                Some(field) if matches!(field.typ, Some(Type::List(_))) => {
+                    *items = field.field_name.clone();
+                }
+                Some(_) => items.to_syn_error("expect a list field").to_err()?,
+                None => items
+                    .to_syn_error("no such field in response data")
+                    .to_err()?,
+            }
+        }
+        Ok(())
+    }
+}
+
 impl ApiResponseData {
     fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
         if let Some(ident) =
@@ -708,7 +2338,7 @@ impl ApiResponseData {
         {
             let extend = BracedConfig::peek_and_parse_extend(input)?;
             let data = BracedConfig::parse(input, ident.span(), extend, true, true, true)?;
-            Ok(Some(Self {
+            let data = Self {
                 data_type: match ident.to_string().as_str() {
                     "json" => DataType::Json(ident.span()),
                     "form" => DataType::Form(ident.span()),
@@ -718,11 +2348,26 @@ impl ApiResponseData {
                     }
                 },
                 data,
-            }))
+            };
+            data.check_defaults()?;
+            Ok(Some(data))
         } else {
             Ok(None)
         }
     }
+
+    fn check_defaults(&self) -> syn::Result<()> {
+        for field in self.data.fields.iter() {
+            if let Some(expr) = &field.expr {
+                if !expr.is_constant() {
+                    expr.to_span()
+                        .to_syn_error("default of response field must be a constant expression")
+                        .to_err()?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl BracedConfig {
@@ -747,10 +2392,11 @@ impl BracedConfig {
     ) -> syn::Result<Self> {
         let inner: ParseBuffer;
         let brace = syn::braced!(inner in input);
+        let attrs = parse_struct_attrs(&inner)?;
         let mut fields: Vec<Field> = vec![];
         let mut removed_fields = HashSet::new();
         while !inner.is_empty() {
-            if let Some(_) = inner.try_parse_comma() {
+            if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
                 continue;
             }
             let mut removal = false;
@@ -790,16 +2436,33 @@ impl BracedConfig {
             extend,
             struct_name: ("_", token).to_ident(),
             brace,
+            attrs,
             fields,
             removed_fields,
         })
     }
 
-    fn resolve_types(&mut self, name: Ident) -> syn::Result<()> {
+    fn check_not_empty(&self) -> syn::Result<()> {
+        if self.fields.is_empty() {
+            self.brace
+                .span
+                .join()
+                .to_syn_error("empty struct is denied in strict mode")
+                .to_err()?;
+        }
+        for field in self.fields.iter() {
+            if let Some(typ) = &field.typ {
+                typ.check_not_empty()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_types(&mut self, name: Ident, affix: &TypeAffix) -> syn::Result<()> {
         let prefix = name.to_string();
-        self.struct_name = name.clone();
+        self.struct_name = affix.apply(name);
         for field in self.fields.iter_mut() {
-            field.resolve_field_type(&prefix)?;
+            field.resolve_field_type(&prefix, affix)?;
         }
         Ok(())
     }
@@ -807,28 +2470,36 @@ impl BracedConfig {
     fn extend_templates(&mut self, templates: &HashMap<Ident, DataTemplate>) -> syn::Result<()> {
         if let Some(extend) = &self.extend {
             if let Some(template) = templates.get(extend) {
-                for field in template.fields.fields.iter().rev() {
-                    if self.removed_fields.contains(&field.name) {
-                        continue;
-                    }
-                    if let Some((index, _)) = self
-                        .fields
-                        .iter()
-                        .enumerate()
-                        .find(|(_, f)| f.name.eq(&field.name))
-                    {
-                        let field = self.fields.remove(index);
-                        self.fields.insert(0, field);
-                    } else {
-                        self.fields.insert(0, field.clone());
-                    }
-                }
+                self.extend_fields(&template.fields);
+            } else {
+                extend.to_syn_error("no such template").to_err()?;
             }
         }
 
         Ok(())
     }
 
+    // inherited fields go first in the template's order, a redefined field
+    // takes the place of the inherited one and `-Field` drops it
+    fn extend_fields(&mut self, template: &BracedConfig) {
+        for field in template.fields.iter().rev() {
+            if self.removed_fields.contains(&field.name) {
+                continue;
+            }
+            if let Some((index, _)) = self
+                .fields
+                .iter()
+                .enumerate()
+                .find(|(_, f)| f.name.eq(&field.name))
+            {
+                let field = self.fields.remove(index);
+                self.fields.insert(0, field);
+            } else {
+                self.fields.insert(0, field.clone());
+            }
+        }
+    }
+
     fn collect_vars<C: VariableCollector>(
         &self,
         vars: &mut C,
@@ -838,7 +2509,7 @@ impl BracedConfig {
         for f in self.fields.iter() {
             if let Some(x) = &f.expr {
                 x.collect_vars(vars, f.typ.as_ref())?;
-            } else {
+            } else if f.readonly.is_none() {
                 if let Some(typ) = &f.typ {
                     match typ {
                         Type::Constant(_) => {}
@@ -870,22 +2541,25 @@ impl BracedConfig {
 }
 
 impl Field {
-    fn resolve_field_type(&mut self, prefix: &str) -> syn::Result<()> {
+    fn resolve_field_type(&mut self, prefix: &str, affix: &TypeAffix) -> syn::Result<()> {
         if let Some(typ) = self.typ.as_mut() {
             match typ {
                 Type::Object(obj) => {
-                    obj.resolve_type_name(&self.field_name, prefix, false)?;
+                    obj.resolve_type_name(&self.field_name, prefix, false, affix)?;
                 }
                 Type::JsonText(JsonStringType { typ, .. }) => {
                     if let Type::Object(obj) = typ.as_mut() {
-                        obj.resolve_type_name(&self.field_name, prefix, false)?;
+                        obj.resolve_type_name(&self.field_name, prefix, false, affix)?;
                     }
                 }
-                Type::List(ListType { element_type, .. }) => {
-                    if let Type::Object(obj) = element_type.as_mut() {
-                        obj.resolve_type_name(&self.field_name, prefix, true)?;
+                Type::List(ListType { element_type, .. }) => match element_type.as_mut() {
+                    Type::Object(obj) => {
+                        obj.resolve_type_name(&self.field_name, prefix, true, affix)?;
                     }
-                }
+                    Type::Enum(e) => e.resolve_type_name(&self.field_name, prefix, affix),
+                    _ => {}
+                },
+                Type::Enum(e) => e.resolve_type_name(&self.field_name, prefix, affix),
                 Type::Datetime(DateTimeType { format, .. }) => {
                     if let Some(format) = format {
                         format.mod_name = self
@@ -909,17 +2583,18 @@ impl ObjectType {
         field_name: &Ident,
         prefix: &str,
         _is_list_item: bool,
+        affix: &TypeAffix,
     ) -> syn::Result<()> {
-        self.struct_name = field_name
+        let name = field_name
             .to_ident_with_case(Case::UpperCamel)
             .with_prefix(prefix);
-        let obj_name = self.struct_name.to_string();
+        let obj_name = name.to_string();
+        self.struct_name = affix.apply(name);
         for child in self.fields.iter_mut() {
-            child.resolve_field_type(&obj_name)?;
+            child.resolve_field_type(&obj_name, affix)?;
         }
         Ok(())
     }
-
     fn collect_vars<C: VariableCollector>(&self, vars: &mut C) -> syn::Result<()> {
         for f in self.fields.iter() {
             if let Some(Expr::Variable(var)) = &f.expr {
@@ -935,7 +2610,7 @@ impl ObjectType {
     fn get_unassigned_fields(&self, spans: &mut Vec<Span>) -> bool {
         let mut has_unsignned = false;
         for field in &self.fields {
-            if field.expr.is_none() {
+            if field.expr.is_none() && field.readonly.is_none() {
                 if let Some(typ) = field.typ.as_ref() {
                     match typ {
                         Type::Constant(_) => {}
@@ -954,13 +2629,151 @@ impl ObjectType {
     }
 }
 
+impl EnumType {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if input.peek(Token![enum]) {
+            let token = input.parse::<Token![enum]>()?.span;
+            let inner: ParseBuffer;
+            let paren = syn::parenthesized!(inner in input);
+            let variants = inner
+                .parse_terminated(Self::parse_variant, Token![,])?
+                .into_iter()
+                .collect::<Vec<_>>();
+            if variants.is_empty() {
+                paren
+                    .span
+                    .join()
+                    .to_syn_error("expect at least one enum value")
+                    .to_err()?;
+            }
+            let mut names = HashSet::new();
+            let mut named_variants = vec![];
+            let mut variant_fields = HashMap::new();
+            for (variant, fields) in variants.into_iter() {
+                let name = variant
+                    .value()
+                    .replace(|c: char| !c.is_alphanumeric(), "_")
+                    .to_case(Case::UpperCamel);
+                // numeric codes like "1" become `V1`
+                let name = if name.starts_with(|c: char| c.is_ascii_digit()) {
+                    format!("V{name}")
+                } else {
+                    name
+                };
+                if syn::parse_str::<Ident>(&name).is_err() {
+                    variant
+                        .to_syn_error("cannot make a variant name from this value")
+                        .to_err()?;
+                }
+                if name.eq("Unknown") {
+                    variant
+                        .to_syn_error("'Unknown' is reserved for unlisted values")
+                        .to_err()?;
+                }
+                if !names.insert(name.clone()) {
+                    variant.to_syn_error("duplicated enum value").to_err()?;
+                }
+                let name = (name, variant.span()).to_ident();
+                if let Some(fields) = fields {
+                    variant_fields.insert(name.clone(), fields);
+                }
+                named_variants.push((name, variant));
+            }
+            Ok(Some(Self {
+                token,
+                paren,
+                enum_name: Ident::new("_", token),
+                variants: named_variants,
+                variant_fields,
+                json_case: None,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // `"code"` or `"code" { name: type, .. }`
+    fn parse_variant(input: ParseStream) -> syn::Result<(LitStr, Option<VariantFields>)> {
+        let code = input.parse::<LitStr>()?;
+        if !input.peek(syn::token::Brace) {
+            return Ok((code, None));
+        }
+        let inner: ParseBuffer;
+        let brace = syn::braced!(inner in input);
+        let mut fields: VariantFields = vec![];
+        for (name, typ) in inner.parse_terminated(
+            |input| {
+                let name = input.parse::<Ident>()?;
+                input.parse::<Token![:]>()?;
+                Ok((name, Type::parse(input)?))
+            },
+            Token![,],
+        )? {
+            if !name.to_string().is_case(Case::Snake) {
+                name.to_syn_error("expect 'snake' case name").to_err()?;
+            }
+            if !matches!(
+                typ,
+                Type::String(_) | Type::Bool(_) | Type::Integer(_) | Type::Float(_)
+            ) {
+                typ.to_span()
+                    .to_syn_error("expect a string, bool, integer or float field")
+                    .to_err()?;
+            }
+            if fields.iter().any(|(prev, _)| prev.eq(&name)) {
+                name.to_syn_error("duplicated variant field").to_err()?;
+            }
+            fields.push((name, typ));
+        }
+        if fields.is_empty() {
+            brace
+                .span
+                .join()
+                .to_syn_error("expect at least one variant field")
+                .to_err()?;
+        }
+        Ok((code, Some(fields)))
+    }
+
+    fn resolve_type_name(&mut self, field_name: &Ident, prefix: &str, affix: &TypeAffix) {
+        self.enum_name = affix.apply(
+            field_name
+                .to_ident_with_case(Case::UpperCamel)
+                .with_prefix(prefix),
+        );
+    }
+}
+
+impl TypeAffix {
+    pub(crate) fn apply(&self, name: Ident) -> Ident {
+        let mut name = name;
+        if let Some(prefix) = &self.prefix {
+            name = name.with_prefix(prefix.to_string());
+        }
+        if let Some(suffix) = &self.suffix {
+            name = name.with_suffix(suffix.to_string());
+        }
+        name
+    }
+}
+
+// `#![...]` inside a block applies to the struct generated from it
+fn parse_struct_attrs(input: ParseStream) -> syn::Result<Vec<Attribute>> {
+    let mut attrs = input.call(Attribute::parse_inner)?;
+    for attr in attrs.iter_mut() {
+        attr.style = AttrStyle::Outer;
+    }
+    Ok(attrs)
+}
+
 impl Parse for ObjectType {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let inner: ParseBuffer;
         let brace = syn::braced!(inner in input);
+        let attrs = parse_struct_attrs(&inner)?;
         let mut fields: Vec<Field> = vec![];
         while !inner.is_empty() {
-            if let Some(_) = inner.try_parse_comma() {
+            if inner.try_parse_comma().is_some() || inner.try_parse_semi().is_some() {
                 continue;
             }
             let field = Field::parse(&inner, true, true, false)?;
@@ -975,11 +2788,53 @@ impl Parse for ObjectType {
         Ok(Self {
             struct_name: Ident::new("_", brace.span.close()),
             brace,
+            attrs,
             fields,
         })
     }
 }
 impl Type {
+    // how the type reads in diagnostics
+    fn describe(&self) -> String {
+        match self {
+            Self::Constant(_) => "constant".to_owned(),
+            Self::String(_) => "string".to_owned(),
+            Self::Bool(_) => "bool".to_owned(),
+            Self::Integer(i) => format!("integer `{}`", i.token),
+            Self::Float(f) => format!("float `{}`", f.token),
+            Self::Object(_) => "object".to_owned(),
+            Self::Datetime(_) => "datetime (as in `datetime(..)`/`timestamp(..)`)".to_owned(),
+            Self::JsonText(_) => "json string".to_owned(),
+            Self::Map(_) => "json object (as in `json(..)`)".to_owned(),
+            Self::List(l) => format!("list of {}", l.element_type.describe()),
+            Self::Enum(_) => "enum".to_owned(),
+            Self::File(_) => "file".to_owned(),
+        }
+    }
+
+    fn check_not_empty(&self) -> syn::Result<()> {
+        match self {
+            Self::Object(obj) => {
+                if obj.fields.is_empty() {
+                    obj.brace
+                        .span
+                        .join()
+                        .to_syn_error("empty struct is denied in strict mode")
+                        .to_err()?;
+                }
+                for field in obj.fields.iter() {
+                    if let Some(typ) = &field.typ {
+                        typ.check_not_empty()?;
+                    }
+                }
+                Ok(())
+            }
+            Self::JsonText(JsonStringType { typ, .. }) => typ.check_not_empty(),
+            Self::List(ListType { element_type, .. }) => element_type.check_not_empty(),
+            _ => Ok(()),
+        }
+    }
+
     fn peek(input: ParseStream) -> syn::Result<()> {
         if !input.peek(syn::token::Brace) {
             input.parse::<Token![:]>()?;
@@ -1004,6 +2859,10 @@ impl Type {
             Self::Float(float)
         } else if let Some(datetime) = DateTimeType::try_parse(input)? {
             Self::Datetime(datetime)
+        } else if let Some(e) = EnumType::try_parse(input)? {
+            Self::Enum(e)
+        } else if let Some(file) = FileType::try_parse(input)? {
+            Self::File(file)
         } else if let Some(constant) = Constant::try_parse(input)? {
             Self::Constant(constant)
         } else {
@@ -1048,6 +2907,11 @@ impl ToSpan for Type {
             Self::JsonText(j) => j.span,
             Self::Map(s) => *s,
             Self::List(l) => (l.element_type.to_span(), l.bracket.span.close()).to_span(),
+            Self::Enum(e) => (e.token, e.paren.span.close()).to_span(),
+            Self::File(f) => f
+                .mime
+                .as_ref()
+                .map_or(f.span, |mime| (f.span, mime.span()).to_span()),
         }
     }
 }
@@ -1080,6 +2944,10 @@ impl IntegerType {
     fn is_u64(&self) -> bool {
         self.token.eq("uint") || self.token.eq("u64")
     }
+
+    fn is_unsigned(&self) -> bool {
+        self.token.to_string().starts_with('u')
+    }
 }
 
 impl IntLimits {
@@ -1164,6 +3032,28 @@ impl FloatLimits {
             let inner: ParseBuffer;
             let paren = syn::parenthesized!(inner in input);
             let limits = inner.parse_terminated(ExprRange::parse, Token![,])?;
+            for range in limits.iter() {
+                for bound in range.start.iter().chain(range.end.iter()) {
+                    // `1.0` or `-1.0`, checked against an `f32`/`f64` value
+                    let lit = match bound.as_ref() {
+                        syn::Expr::Unary(syn::ExprUnary {
+                            op: syn::UnOp::Neg(_),
+                            expr,
+                            ..
+                        }) => expr.as_ref(),
+                        expr => expr,
+                    };
+                    if !matches!(
+                        lit,
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Float(_),
+                            ..
+                        })
+                    ) {
+                        bound.span().to_syn_error("expect float value").to_err()?;
+                    }
+                }
+            }
             Ok(Some(Self { paren, limits }))
         } else {
             Ok(None)
@@ -1204,6 +3094,40 @@ impl DateTimeFormat {
     }
 }
 
+impl FileType {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        let Some(file) = input.try_parse_as_ident("file", false) else {
+            return Ok(None);
+        };
+        let mime = if input.peek(syn::token::Paren) {
+            let inner: ParseBuffer;
+            syn::parenthesized!(inner in input);
+            let mime = inner.parse::<LitStr>()?;
+            // checked here, so setting it on the part cannot fail
+            let token = |s: &str| {
+                !s.is_empty()
+                    && s.chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+            };
+            if !mime
+                .value()
+                .split_once('/')
+                .is_some_and(|(typ, subtype)| token(typ) && token(subtype))
+            {
+                mime.to_syn_error("expect a mime type like \"image/png\"")
+                    .to_err()?;
+            }
+            Some(mime)
+        } else {
+            None
+        };
+        Ok(Some(Self {
+            span: file.span(),
+            mime,
+        }))
+    }
+}
+
 impl DateTimeType {
     fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
         if let Some(ident) = input.try_parse_one_of_idents(("datetime", "date")) {
@@ -1225,7 +3149,12 @@ impl Field {
         parse_assignment: bool,
     ) -> syn::Result<Self> {
         let name = input.parse_as_lit_str()?;
+        let mut input_keys = parse_input_keys(input, &name)?;
         let optional = input.try_parse_question();
+        // also accepted behind the marker, like `Name? <- [..]`
+        if input_keys.is_empty() && optional.is_some() {
+            input_keys = parse_input_keys(input, &name)?;
+        }
 
         let typ = if parse_type {
             Type::peek(input)?;
@@ -1233,6 +3162,118 @@ impl Field {
         } else {
             None
         };
+        let mut readonly = None;
+        let mut writeonly = None;
+        let mut example = None;
+        let mut require = None;
+        let mut secret = None;
+        let mut int_or_str = None;
+        while let Some(marker) = input.try_parse_one_of_idents((
+            "readonly",
+            "writeonly",
+            "example",
+            "require",
+            "secret",
+            "int_or_str",
+        )) {
+            if marker.to_string().eq("int_or_str") {
+                if let Some(prev) = int_or_str {
+                    (prev, marker.span())
+                        .to_span()
+                        .to_syn_error("duplicated int_or_str marker")
+                        .to_err()?;
+                }
+                if !matches!(typ, Some(Type::Integer(_) | Type::Enum(_))) {
+                    marker
+                        .to_syn_error("int_or_str expects an integer or enum field")
+                        .to_err()?;
+                }
+                // their variants are objects, never a bare code
+                if matches!(&typ, Some(Type::Enum(e)) if !e.variant_fields.is_empty()) {
+                    marker
+                        .to_syn_error("int_or_str expects an enum without variant fields")
+                        .to_err()?;
+                }
+                int_or_str = Some(marker.span());
+                continue;
+            }
+            if marker.to_string().eq("secret") {
+                if let Some(prev) = secret {
+                    (prev, marker.span())
+                        .to_span()
+                        .to_syn_error("duplicated secret marker")
+                        .to_err()?;
+                }
+                secret = Some(marker.span());
+                continue;
+            }
+            if marker.to_string().eq("require") {
+                if let Some(prev) = require {
+                    (prev, marker.span())
+                        .to_span()
+                        .to_syn_error("duplicated require marker")
+                        .to_err()?;
+                }
+                if let Some(optional) = optional {
+                    (optional.span(), marker.span())
+                        .to_span()
+                        .to_syn_error("a required field cannot be optional")
+                        .to_err()?;
+                }
+                require = Some(marker.span());
+                continue;
+            }
+            if marker.to_string().eq("example") {
+                if example.is_some() {
+                    marker.to_syn_error("duplicated example").to_err()?;
+                }
+                input.parse::<Token![=]>()?;
+                let value = input.parse::<Constant>()?;
+                if let Some(t) = typ.as_ref() {
+                    if !is_type_and_constant_match(t, &value) {
+                        (t.to_span(), value.span())
+                            .to_span()
+                            .to_syn_error("unmatch type with example")
+                            .to_err()?;
+                    }
+                }
+                example = Some(value);
+                continue;
+            }
+            if let Some(prev) = readonly.or(writeonly) {
+                (prev, marker.span())
+                    .to_span()
+                    .to_syn_error("conflicted or duplicated readonly/writeonly marker")
+                    .to_err()?;
+            }
+            if marker.to_string().eq("readonly") {
+                readonly = Some(marker.span());
+            } else {
+                writeonly = Some(marker.span());
+            }
+        }
+        // an ident here is a misspelled marker rather than the next field,
+        // which would go on with `:`, `?`, `=` or braces
+        if typ.is_some()
+            && input.peek(Ident)
+            && !(input.peek2(Token![:])
+                || input.peek2(Token![?])
+                || input.peek2(Token![=])
+                || input.peek2(Brace))
+        {
+            let marker = input.parse::<Ident>()?;
+            marker
+                .to_syn_error(format!(
+                    "unknown marker `{marker}`, expect readonly, writeonly, example, require, secret or int_or_str"
+                ))
+                .to_err()?;
+        }
+        if let (Some(require), Some(writeonly)) = (require, writeonly) {
+            (require, writeonly)
+                .to_span()
+                .to_syn_error("a writeonly field is never decoded")
+                .to_err()?;
+        }
         let alias = if parse_alias {
             if input.peek(Token![->]) {
                 input.parse::<Token![->]>()?;
@@ -1243,15 +3284,42 @@ impl Field {
         } else {
             None
         };
+        let mut attrs = input.call(Attribute::parse_outer)?;
+        let mut json_path = None;
         let expr = if parse_assignment {
             if let Some(_eq) = input.try_parse_eq() {
-                Some(Expr::parse(input)?)
+                if input.peek(Token![$]) && input.peek2(Token![.]) {
+                    json_path = Some(input.parse::<JsonPath>()?);
+                    // `$.data.size || 10`, used when the path is missing
+                    if input.peek(Token![||]) {
+                        input.parse::<Token![||]>()?;
+                        Some(Expr::parse(input)?)
+                    } else {
+                        None
+                    }
+                } else {
+                    Some(Expr::parse(input)?)
+                }
             } else {
                 None
             }
         } else {
             None
         };
+        let condition = if expr.is_some() && input.peek(Token![if]) {
+            let token = input.parse::<Token![if]>()?;
+            let var = input.parse::<Variable>()?;
+            if !var.client_option {
+                (token.span, var.to_span())
+                    .to_span()
+                    .to_syn_error("condition must be a client option like `$$flag`")
+                    .to_err()?;
+            }
+            Some(var)
+        } else {
+            None
+        };
+        attrs.extend(input.call(Attribute::parse_outer)?);
         let mut field_name = if let Some(alias) = &alias {
             if alias.is_keyword() {
                 alias
@@ -1275,6 +3343,36 @@ impl Field {
             }
         }
 
+        let mut expr = expr;
+        if let Some(expr) = expr.as_mut() {
+            expr.resolve_optional_var(optional.as_ref().map(|o| o.span()))?;
+        }
+        // a conditional field may be left out, so it is optional anyway
+        let optional = optional
+            .map(|o| o.span())
+            .or(condition.as_ref().map(|var| var.dollar));
+
+        if let (Some(Type::Integer(i)), Some(Expr::Constant(c))) = (typ.as_ref(), expr.as_ref()) {
+            if i.is_unsigned() && c.is_negative() {
+                (i.token.span(), c.span())
+                    .to_span()
+                    .to_syn_error("negative value for unsigned integer type")
+                    .to_err()?;
+            }
+        }
+
+        match (typ.as_ref(), expr.as_ref()) {
+            (Some(t @ (Type::List(_) | Type::Object(_))), Some(Expr::Constant(c))) => {
+                check_constant_for_type(t, c)?;
+            }
+            (Some(Type::Constant(Constant::Array(arr))), _) if arr.elements.is_empty() => {
+                arr.span
+                    .to_syn_error("cannot infer element type of empty array")
+                    .to_err()?;
+            }
+            _ => {}
+        }
+
         let mut default = None;
         if let Some(Type::Constant(c)) = typ.as_ref() {
             default = Some(c.to_value());
@@ -1285,16 +3383,59 @@ impl Field {
         }
         Ok(Self {
             name,
+            input_keys,
             field_name,
-            optional: optional.map(|o| o.span()),
+            optional,
             typ,
+            readonly,
+            writeonly,
+            example,
+            require,
+            secret,
+            int_or_str,
             alias,
             expr,
+            condition,
+            json_path,
+            cow: false,
+            stringify: false,
+            skip_empty: false,
             default,
+            attrs,
         })
     }
 }
 
+fn parse_input_keys(input: ParseStream, name: &LitStr) -> syn::Result<Vec<LitStr>> {
+    if !input.peek(Token![<-]) {
+        return Ok(vec![]);
+    }
+    let arrow = input.parse::<Token![<-]>()?;
+    let inner: ParseBuffer;
+    let bracket = syn::bracketed!(inner in input);
+    let keys = inner
+        .parse_terminated(|input| input.parse::<LitStr>(), Token![,])?
+        .into_iter()
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        (arrow.spans[0], bracket.span.join())
+            .to_span()
+            .to_syn_error("expect at least one input key")
+            .to_err()?;
+    }
+    let mut seen = HashSet::new();
+    for key in keys.iter() {
+        if !seen.insert(key.value()) {
+            key.to_syn_error("duplicated input key").to_err()?;
+        }
+    }
+    // the name itself is already the primary key
+    Ok(keys
+        .into_iter()
+        .filter(|key| key.value() != name.value())
+        .collect())
+}
+
 impl Field {
     fn requires_to_simple_type(&self) -> syn::Result<()> {
         if let Some(t) = self.typ.as_ref() {
@@ -1325,19 +3466,62 @@ impl Field {
 fn is_type_and_constant_match(t: &Type, c: &Constant) -> bool {
     match (t, c) {
         (Type::String(_), Constant::String(_)) => true,
-        (Type::Integer(_), Constant::Int(_)) => true,
+        (Type::Integer(i), c @ Constant::Int(_)) => !(i.is_unsigned() && c.is_negative()),
         (Type::Float(_), Constant::Float(_)) => true,
         (Type::Bool(_), Constant::Bool(_)) => true,
+        (Type::Map(_), Constant::Object(_)) => true,
+        (Type::List(ListType { element_type, .. }), Constant::Array(arr)) => arr
+            .elements
+            .iter()
+            .all(|c| is_type_and_constant_match(element_type, c)),
         _ => false,
     }
 }
 
+// object constants set fields of the declared object by name, every key must
+// be one of its fields and take a value of the field's type
+fn check_constant_for_type(t: &Type, c: &Constant) -> syn::Result<()> {
+    match (t, c) {
+        (Type::Object(obj), Constant::Object(ObjectConstant { fields, .. })) => {
+            for ObjectConstantField { name, value } in fields.iter() {
+                let Some(field) = obj.field_of_key(name) else {
+                    return name
+                        .to_syn_error(format!("no field `{name}` in the object type"))
+                        .to_err();
+                };
+                match &field.typ {
+                    Some(typ) => check_constant_for_type(typ, value)?,
+                    // untyped fields are strings
+                    None if matches!(value, Constant::String(_)) => {}
+                    None => value
+                        .span()
+                        .to_syn_error("unmatch type with value, expect string")
+                        .to_err()?,
+                }
+            }
+            Ok(())
+        }
+        (
+            Type::List(ListType { element_type, .. }),
+            Constant::Array(ConstantArray { elements, .. }),
+        ) => elements
+            .iter()
+            .try_for_each(|el| check_constant_for_type(element_type, el)),
+        _ if is_type_and_constant_match(t, c) => Ok(()),
+        _ => c
+            .span()
+            .to_syn_error(format!("unmatch type with value, expect {}", t.describe()))
+            .to_err(),
+    }
+}
+
 fn is_type_and_value_match(t: &Type, x: &Expr) -> bool {
     match (t, x) {
         (Type::String(_), Expr::Json(_)) => true,
         (Type::String(_), Expr::Datetime(_)) => true,
         (Type::String(_), Expr::Format(_)) => true,
         (Type::String(_), Expr::Join(_)) => true,
+        (Type::String(_), Expr::Transform(_)) => true,
         (Type::Integer(i), Expr::Timestamp(_)) => i.is_u64(),
         (t, Expr::Constant(c)) => is_type_and_constant_match(t, c),
         (t, Expr::Or(OrExpr { default, .. })) => is_type_and_constant_match(t, default),
@@ -1362,6 +3546,8 @@ impl Parse for Expr {
             Self::Format(string)
         } else if let Some(string) = JoinStringFn::try_parse(input)? {
             Self::Join(string)
+        } else if let Some(string) = StringTransformFn::try_parse(input)? {
+            Self::Transform(string)
         } else if let Some(uint) = UnixTimestampUintFn::try_parse(input)? {
             Self::Timestamp(uint)
         } else if let Some(ident) = input.try_parse_as_ident("default", false) {
@@ -1385,13 +3571,329 @@ impl ToSpan for Expr {
             Self::Datetime(x) => x.to_span(),
             Self::Timestamp(x) => x.to_span(),
             Self::Join(x) => x.to_span(),
+            Self::Transform(x) => x.to_span(),
             Self::Or(x) => x.to_span(),
             Expr::Default(span) => *span,
         }
     }
 }
 
+impl BracedConfig {
+    // header values go out as strings, literals are turned into strings here
+    // and other scalar arguments are converted when the headers are built
+    fn check_header_values(
+        &mut self,
+        variables: &[Variable],
+        options: &HashMap<Ident, Field>,
+    ) -> syn::Result<()> {
+        let unsupported = |typ: &Type| match typ {
+            Type::Enum(e) => !e.variant_fields.is_empty(),
+            _ => matches!(
+                typ,
+                Type::Object(_)
+                    | Type::Map(_)
+                    | Type::List(_)
+                    | Type::Constant(Constant::Object(_) | Constant::Array(_))
+            ),
+        };
+        for field in self.fields.iter_mut() {
+            if let Some(typ) = &field.typ {
+                if unsupported(typ) {
+                    typ.to_span()
+                        .to_syn_error("header value must be convertible to a string")
+                        .to_err()?;
+                }
+                continue;
+            }
+            match &mut field.expr {
+                Some(Expr::Constant(c)) => {
+                    let value = match &*c {
+                        Constant::Bool(b) => b.value.to_string(),
+                        Constant::Int(i) => i.base10_digits().to_owned(),
+                        Constant::Float(f) => f.base10_digits().to_owned(),
+                        Constant::String(_) => continue,
+                        other => other
+                            .span()
+                            .to_syn_error("header value must be convertible to a string")
+                            .to_err()?,
+                    };
+                    *c = Constant::String(LitStr::new(&value, c.span()));
+                    field.default = Some(c.to_value());
+                }
+                Some(Expr::Variable(var)) => {
+                    let typ = if var.client_option {
+                        options.get(&var.name).and_then(|opt| opt.typ.as_ref())
+                    } else {
+                        variables
+                            .iter()
+                            .find(|other| other.name.eq(&var.name) && !other.client_option)
+                            .and_then(|other| other.typ.as_ref())
+                    };
+                    match typ {
+                        None | Some(Type::String(_)) | Some(Type::JsonText(_)) => {}
+                        Some(typ) if unsupported(typ) => {
+                            (var.to_span(), typ.to_span())
+                                .to_span()
+                                .to_syn_error("header value must be convertible to a string")
+                                .to_err()?;
+                        }
+                        Some(_) => field.stringify = true,
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+fn visit_field_types(fields: &mut [Field], visit: &mut impl FnMut(&mut Type)) {
+    for field in fields.iter_mut() {
+        if let Some(typ) = &mut field.typ {
+            visit(typ);
+        }
+    }
+}
+
+// the options holding a plain value, where a second one would silently
+// override the first; rejected like the duplicated `Option` ones
+fn check_duplicated_option(seen: &mut HashSet<String>, ident: &Ident) -> syn::Result<()> {
+    if !seen.insert(ident.to_string().to_lowercase()) {
+        ident
+            .to_syn_error(format!("duplicated {ident} config"))
+            .to_err()?;
+    }
+    Ok(())
+}
+
+// bare `uint`, `int` and `integer` become `u64`/`i64`, or `u32`/`i32`
+// with `default_int_width: 32`
+fn resolve_int_type(typ: &mut Type, narrow: bool) {
+    match typ {
+        Type::Integer(i) => {
+            let resolved = match (i.token.to_string().as_str(), narrow) {
+                ("uint", false) => "u64",
+                ("int" | "integer", false) => "i64",
+                ("uint", true) => "u32",
+                ("int" | "integer", true) => "i32",
+                _ => return,
+            };
+            i.token = (resolved, i.token.span()).to_ident();
+        }
+        Type::Object(obj) => {
+            visit_field_types(&mut obj.fields, &mut |typ| resolve_int_type(typ, narrow))
+        }
+        Type::List(ListType { element_type, .. }) => resolve_int_type(element_type, narrow),
+        Type::JsonText(JsonStringType { typ, .. }) => resolve_int_type(typ, narrow),
+        Type::Enum(e) => e
+            .variant_fields
+            .values_mut()
+            .flatten()
+            .for_each(|(_, typ)| resolve_int_type(typ, narrow)),
+        _ => {}
+    }
+}
+
+fn set_json_case(typ: &mut Type, case: &LitStr) {
+    match typ {
+        Type::Enum(e) => e.json_case = Some(case.clone()),
+        Type::Object(obj) => {
+            visit_field_types(&mut obj.fields, &mut |typ| set_json_case(typ, case))
+        }
+        Type::List(ListType { element_type, .. }) => set_json_case(element_type, case),
+        Type::JsonText(JsonStringType { typ, .. }) => set_json_case(typ, case),
+        _ => {}
+    }
+}
+
+// optional lists are already left out as `None`
+fn mark_skip_empty_lists(fields: &mut [Field]) {
+    for field in fields.iter_mut() {
+        match &mut field.typ {
+            Some(Type::List(_)) => field.skip_empty = field.optional.is_none(),
+            Some(Type::Object(obj)) => mark_skip_empty_lists(&mut obj.fields),
+            _ => {}
+        }
+    }
+}
+
+// milliseconds, or a string with a `ms`, `s`, `m` or `h` unit
+fn parse_timeout(input: ParseStream) -> syn::Result<LitInt> {
+    let (millis, span) = if input.peek(LitInt) {
+        let millis = input.parse::<LitInt>()?;
+        (millis.base10_parse::<u64>()?, millis.span())
+    } else {
+        let timeout = input.parse::<LitStr>()?;
+        let value = timeout.value();
+        let value = value.trim();
+        let digits = value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let scale = match &value[digits..] {
+            "ms" => Some(1),
+            "s" => Some(1000),
+            "m" => Some(60 * 1000),
+            "h" => Some(60 * 60 * 1000),
+            _ => None,
+        };
+        let millis = value[..digits]
+            .parse::<u64>()
+            .ok()
+            .zip(scale)
+            .and_then(|(count, scale)| count.checked_mul(scale));
+        let Some(millis) = millis else {
+            return timeout
+                .to_syn_error("expect a duration like \"500ms\", \"30s\", \"5m\" or \"1h\"")
+                .to_err();
+        };
+        (millis, timeout.span())
+    };
+    if millis == 0 {
+        span.to_syn_error("expect a non-zero timeout").to_err()?;
+    }
+    Ok(LitInt::new(&format!("{millis}u64"), span))
+}
+
+fn mark_cow_strings(fields: &mut [Field]) {
+    for field in fields.iter_mut() {
+        match &mut field.typ {
+            Some(Type::String(_) | Type::Constant(Constant::String(_))) => field.cow = true,
+            Some(Type::Object(obj)) => mark_cow_strings(&mut obj.fields),
+            _ => {}
+        }
+    }
+}
+
+// maps `$Key` to the variable assigned to the sibling field `Key`
+fn resolve_field_refs(fields: &mut [Field]) {
+    let assigned = fields
+        .iter()
+        .filter_map(|field| match &field.expr {
+            Some(Expr::Variable(var)) if !var.client_option => {
+                Some((field.name.value(), var.clone()))
+            }
+            _ => None,
+        })
+        .collect::<HashMap<_, _>>();
+    let resolve = |name: &Ident| {
+        let mut target = assigned.get(&name.to_string())?;
+        // follow `A = $B, B = $c`, a cycle stops after every field is visited
+        for _ in 0..assigned.len() {
+            match assigned.get(&target.name.to_string()) {
+                Some(next) if next.name.ne(&target.name) => target = next,
+                _ => break,
+            }
+        }
+        Some(target)
+    };
+    let mut refs = vec![];
+    for field in fields.iter_mut() {
+        if let Some(expr) = &mut field.expr {
+            for (var, _) in expr.variables_mut() {
+                if var.client_option {
+                    continue;
+                }
+                if let Some(target) = resolve(&var.name) {
+                    if target.name.ne(&var.name) {
+                        refs.push((var, target));
+                    }
+                }
+            }
+        }
+    }
+    for (var, target) in refs {
+        var.name = Ident::new(&target.name.to_string(), var.name.span());
+        if var.typ.is_none() {
+            var.typ = target.typ.clone();
+        }
+    }
+}
+
+fn collect_field_vars_mut<'a>(fields: &'a mut [Field], usages: &mut Vec<(&'a mut Variable, bool)>) {
+    for field in fields.iter_mut() {
+        if let Some(expr) = &mut field.expr {
+            usages.extend(expr.variables_mut());
+        }
+        if let Some(Type::Object(obj)) = &mut field.typ {
+            collect_field_vars_mut(&mut obj.fields, usages);
+        }
+    }
+}
+
 impl Expr {
+    // variables in the expression, and whether the generated code moves them
+    fn variables_mut(&mut self) -> Vec<(&mut Variable, bool)> {
+        match self {
+            Self::Variable(var) | Self::Or(OrExpr { variable: var, .. }) => vec![(var, true)],
+            Self::Json(JsonStringifyFn { variable, .. })
+            | Self::Datetime(DatetimeFn { variable, .. })
+            | Self::Timestamp(UnixTimestampUintFn { variable, .. })
+            | Self::Join(JoinStringFn { variable, .. })
+            | Self::Transform(StringTransformFn { variable, .. }) => vec![(variable, false)],
+            Self::Format(FormatFn { args, .. }) => args
+                .iter_mut()
+                .flatten()
+                .flat_map(|arg| arg.variables_mut())
+                .map(|(var, _)| (var, false))
+                .collect(),
+            Self::Constant(_) | Self::Default(_) => vec![],
+        }
+    }
+
+    // a variable assigned directly to an optional field is optional, elsewhere
+    // an optional variable needs a `||` fallback
+    fn resolve_optional_var(&mut self, field_optional: Option<Span>) -> syn::Result<()> {
+        match self {
+            Self::Variable(var) => {
+                if var.optional.is_none() && !var.client_option {
+                    var.optional = field_optional;
+                } else if let (Some(optional), None) = (var.optional, field_optional) {
+                    optional
+                        .to_syn_error(
+                            "optional variable requires an optional field or `||` default",
+                        )
+                        .to_err()?;
+                }
+            }
+            Self::Or(OrExpr { variable, .. }) => {
+                if variable.optional.is_none() && !variable.client_option {
+                    variable.optional = field_optional;
+                }
+            }
+            Self::Format(FormatFn { args, .. }) => {
+                for arg in args.iter_mut().flatten() {
+                    arg.resolve_optional_var(None)?;
+                }
+            }
+            Self::Json(JsonStringifyFn { variable, .. })
+            | Self::Datetime(DatetimeFn { variable, .. })
+            | Self::Timestamp(UnixTimestampUintFn { variable, .. })
+            | Self::Join(JoinStringFn { variable, .. })
+            | Self::Transform(StringTransformFn { variable, .. }) => {
+                if let Some(optional) = variable.optional {
+                    optional
+                        .to_syn_error(
+                            "optional variable requires an optional field or `||` default",
+                        )
+                        .to_err()?;
+                }
+            }
+            Self::Constant(_) | Self::Default(_) => {}
+        }
+        Ok(())
+    }
+
+    // whether the expression can be evaluated without any variable
+    fn is_constant(&self) -> bool {
+        match self {
+            Self::Constant(_) | Self::Default(_) => true,
+            Self::Format(FormatFn { args, .. }) => {
+                args.iter().flatten().all(|arg| arg.is_constant())
+            }
+            _ => false,
+        }
+    }
+
     fn collect_vars<C: VariableCollector>(
         &self,
         vars: &mut C,
@@ -1442,6 +3944,14 @@ impl Expr {
                     })),
                 )?;
             }
+            Expr::Transform(call) => {
+                vars.collect(
+                    &call.variable,
+                    Some(&Type::String(StringType {
+                        span: call.to_span(),
+                    })),
+                )?;
+            }
             Expr::Or(or) => vars.collect(&or.variable, suggested_type)?,
             _ => {}
         }
@@ -1476,21 +3986,30 @@ impl Constant {
     fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
         Ok(if input.peek(LitStr) {
             Some(Self::String(input.parse()?))
-        } else if input.peek(syn::LitInt) {
+        } else if input.peek(syn::LitInt) || (input.peek(Token![-]) && input.peek2(syn::LitInt)) {
             Some(Self::Int(input.parse()?))
-        } else if input.peek(syn::LitFloat) {
+        } else if input.peek(syn::LitFloat) || (input.peek(Token![-]) && input.peek2(syn::LitFloat))
+        {
             Some(Self::Float(input.parse()?))
         } else if input.peek(syn::LitBool) {
             Some(Self::Bool(input.parse()?))
         } else if input.peek(syn::token::Brace) {
             Some(Self::Object(input.parse()?))
-        } else if input.peek(syn::token::Bracket) {
+        } else if input.peek(Bracket) {
             Some(Self::Array(input.parse()?))
         } else {
             None
         })
     }
 
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Constant::Int(i) => i.base10_digits().starts_with('-'),
+            Constant::Float(f) => f.base10_digits().starts_with('-'),
+            _ => false,
+        }
+    }
+
     pub fn span(&self) -> Span {
         match self {
             Constant::String(s) => s.span(),
@@ -1516,18 +4035,21 @@ impl Constant {
             Constant::Bool(c) => c.to_expr(),
             Constant::Int(c) => c.to_expr(),
             Constant::Float(c) => c.to_expr(),
-            Constant::Object(c) => syn::Expr::Call(syn::ExprCall {
+            Constant::Object(_) => syn::Expr::Verbatim(self.to_token_stream()),
+            Constant::Array(c) => syn::Expr::Macro(syn::ExprMacro {
                 attrs: vec![],
-                func: Box::new(syn::Path::from_idents(("Default", "default", c.span)).to_expr()),
-                paren_token: Paren(c.span),
-                args: Punctuated::new(),
+                mac: syn::Macro {
+                    path: syn::Path::from_ident(("vec", c.span)),
+                    bang_token: Token![!](c.span),
+                    delimiter: syn::MacroDelimiter::Bracket(Bracket(c.span)),
+                    tokens: c
+                        .elements
+                        .iter()
+                        .map(|c| c.to_value())
+                        .collect::<Punctuated<_, Token![,]>>()
+                        .to_token_stream(),
+                },
             }),
-            Constant::Array(c) => c
-                .elements
-                .iter()
-                .map(|c| c.to_value())
-                .collect::<Vec<_>>()
-                .to_expr(),
         }
     }
 }
@@ -1573,7 +4095,16 @@ impl Parse for ConstantArray {
                 inner.parse::<Token![,]>()?;
                 continue;
             }
-            elements.push(inner.parse()?);
+            let element: Constant = inner.parse()?;
+            if let Some(first) = elements.first() {
+                if !element.eq(first) {
+                    element
+                        .span()
+                        .to_syn_error("array elements must have the same type")
+                        .to_err()?;
+                }
+            }
+            elements.push(element);
         }
         Ok(Self {
             elements,
@@ -1606,6 +4137,12 @@ impl Variable {
             .unwrap_or(dollar.span());
         let client_option = client_option.is_some();
         let name = input.parse()?;
+        let optional = input.try_parse_question().map(|q| q.span());
+        if let (true, Some(optional)) = (client_option, optional) {
+            optional
+                .to_syn_error("client option cannot be optional variable")
+                .to_err()?;
+        }
         Ok(if input.peek(Token![:]) {
             Type::peek(input)?;
             Self {
@@ -1613,6 +4150,8 @@ impl Variable {
                 name,
                 typ: Some(Type::parse(input)?),
                 client_option,
+                optional,
+                shared: false,
             }
         } else {
             Self {
@@ -1620,6 +4159,8 @@ impl Variable {
                 name,
                 typ: None,
                 client_option,
+                optional,
+                shared: false,
             }
         })
     }
@@ -1635,6 +4176,45 @@ impl ToSpan for Variable {
     }
 }
 
+impl Parse for JsonPath {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let dollar = input.parse::<Token![$]>()?;
+        let mut span = dollar.span;
+        let mut segments = vec![];
+        loop {
+            if let Some(dot) = input.parse::<Option<Token![.]>>()? {
+                let key = if input.peek(LitStr) {
+                    input.parse::<LitStr>()?
+                } else {
+                    let key = input.call(<Ident as syn::ext::IdentExt>::parse_any)?;
+                    LitStr::new(&key.to_string(), key.span())
+                };
+                span = (dot.span, key.span()).to_span();
+                segments.push(JsonPathSegment::Key(key));
+            } else if input.peek(Bracket) {
+                let inner: ParseBuffer;
+                let bracket = syn::bracketed!(inner in input);
+                let index = inner.parse::<LitInt>()?;
+                index.base10_parse::<usize>()?;
+                span = bracket.span.close();
+                segments.push(JsonPathSegment::Index(index));
+            } else {
+                break;
+            }
+        }
+        if segments.is_empty() {
+            dollar
+                .span
+                .to_syn_error("expect a json path like `$.data.token`")
+                .to_err()?;
+        }
+        Ok(Self {
+            span: (dollar.span, span).to_span(),
+            segments,
+        })
+    }
+}
+
 impl Parse for Variable {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let dollar = input.parse()?;
@@ -1670,16 +4250,85 @@ impl FormatFn {
             } else {
                 None
             };
-            Ok(Some(Self {
+            let format = Self {
                 fn_token: ident.span(),
                 paren,
                 format_text,
                 args,
-            }))
+            };
+            format.check_args()?;
+            Ok(Some(format))
         } else {
             Ok(None)
         }
     }
+
+    fn check_args(&self) -> syn::Result<()> {
+        let expected = self.count_placeholders()?;
+        let given = self.args.as_ref().map(|args| args.len()).unwrap_or(0);
+        if expected != given {
+            self.format_text
+                .span()
+                .to_syn_error(format!(
+                    "format string expects {expected} argument(s), but {given} given"
+                ))
+                .to_err()?;
+        }
+        Ok(())
+    }
+
+    // counts the arguments required by `{}`/`{n}` placeholders
+    fn count_placeholders(&self) -> syn::Result<usize> {
+        let text = self.format_text.value();
+        let mut chars = text.chars().peekable();
+        let mut implicit = 0;
+        let mut max_index: Option<usize> = None;
+        while let Some(c) = chars.next() {
+            match c {
+                '{' => {
+                    if chars.peek() == Some(&'{') {
+                        chars.next();
+                        continue;
+                    }
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => self
+                                .format_text
+                                .span()
+                                .to_syn_error("unclosed placeholder in format string")
+                                .to_err()?,
+                        }
+                    }
+                    let arg = placeholder.split(':').next().unwrap_or_default().trim();
+                    if arg.is_empty() {
+                        implicit += 1;
+                    } else if let Ok(index) = arg.parse::<usize>() {
+                        max_index = Some(max_index.map_or(index, |max| max.max(index)));
+                    } else {
+                        self.format_text
+                            .span()
+                            .to_syn_error("named placeholder is not supported, use `{}` or `{n}`")
+                            .to_err()?;
+                    }
+                }
+                '}' => {
+                    if chars.peek() == Some(&'}') {
+                        chars.next();
+                    } else {
+                        self.format_text
+                            .span()
+                            .to_syn_error("unmatched `}` in format string")
+                            .to_err()?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(max_index.map(|max| max + 1).unwrap_or(0).max(implicit))
+    }
 }
 impl ToSpan for FormatFn {
     fn to_span(&self) -> Span {
@@ -1759,6 +4408,37 @@ impl ToSpan for JoinStringFn {
     }
 }
 
+impl StringTransformFn {
+    fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
+        if let Some(ident) = input.try_parse_one_of_idents(("upper", "lower", "trim")) {
+            let transform = match ident.to_string().as_str() {
+                "upper" => StringTransform::Upper,
+                "lower" => StringTransform::Lower,
+                "trim" => StringTransform::Trim,
+                _ => {
+                    unreachable!()
+                }
+            };
+            let inner: ParseBuffer;
+            let paren = syn::parenthesized!(inner in input);
+            let variable = Variable::parse(&inner)?;
+            Ok(Some(Self {
+                token: ident.span(),
+                paren,
+                variable,
+                transform,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+}
+impl ToSpan for StringTransformFn {
+    fn to_span(&self) -> Span {
+        (self.token, self.paren.span.close()).to_span()
+    }
+}
+
 impl UnixTimestampUintFn {
     fn try_parse(input: ParseStream) -> syn::Result<Option<Self>> {
         if let Some(ident) = input.try_parse_one_of_idents(("timestamp", "unix_timestamp")) {
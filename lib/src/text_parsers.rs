@@ -4,9 +4,9 @@ pub mod url_parser {
     use convert_case::{Case, Casing};
     use nom::{
         branch::alt,
-        bytes::complete::{tag, take_while, take_while1},
+        bytes::complete::{tag, take_while, take_while1, take_while_m_n},
         character::complete::{alpha1, alphanumeric1, digit1, one_of},
-        combinator::{map, map_res, opt},
+        combinator::{map, map_res, opt, recognize},
         error::{context, ErrorKind},
         multi::{count, many0, many1, many_m_n},
         sequence::{preceded, separated_pair, terminated, tuple},
@@ -35,7 +35,7 @@ pub mod url_parser {
         let value = api.uri_format.value();
         let span = api.uri_format.span();
         let (
-            _,
+            rest,
             ApiUri {
                 schema,
                 auth,
@@ -46,6 +46,12 @@ pub mod url_parser {
                 fragment,
             },
         ) = uri(&value).map_err(|_| span.to_syn_error("bad url"))?;
+        if rest.starts_with('%') {
+            span.to_syn_error("bad percent-encoding in url").to_err()?;
+        } else if rest.starts_with('$') {
+            span.to_syn_error("bad variable in url, expect `$name` or `${name: type}`")
+                .to_err()?;
+        }
 
         let mut uri_format = schema.map(|s| s.to_owned()).unwrap_or_default();
         api.schema = schema.map(|schema| (schema, span).to_lit_str());
@@ -143,30 +149,55 @@ pub mod url_parser {
                 }
             },
         );
-        api.uri_query = query.map(|UrlQuery { params }| ApiUriQuery {
-            fields: params
+        // query names and values are decoded here, reqwest encodes them again
+        // when the query is sent
+        let decode = |s: &str| percent_decode(s).ok_or_else(|| span.to_syn_error("bad url"));
+        let mut fields = vec![];
+        for Param { name, value } in query.map(|q| q.params).unwrap_or_default() {
+            let name = decode(name)?;
+            let mut default = None;
+            let expr = if let Some(value) = value {
+                Some(match value {
+                    Segment::CodePoints(s) => {
+                        let s = decode(s)?;
+                        default = Some((&s, span).to_lit_str().to_expr());
+                        Expr::Constant(Constant::String((s, span).to_lit_str()))
+                    }
+                    Segment::Variable(v) => Expr::Variable(v.to_variable(span)),
+                })
+            } else {
+                None
+            };
+            fields.push((name, expr, default));
+        }
+        api.uri_query = (!fields.is_empty()).then(|| ApiUriQuery {
+            fields: fields
                 .into_iter()
-                .map(|Param { name, value }| {
-                    let mut default = None;
-                    let expr = if let Some(value) = value {
-                        Some(match value {
-                            Segment::CodePoints(s) => {
-                                default = Some((s, span).to_lit_str().to_expr());
-                                Expr::Constant(Constant::String((s, span).to_lit_str()))
-                            }
-                            Segment::Variable(v) => Expr::Variable(v.to_variable(span)),
-                        })
-                    } else {
-                        None
-                    };
+                .map(|(name, expr, default)| {
+                    let field_name = name
+                        .replace(|c: char| !c.is_alphanumeric(), "_")
+                        .to_case(Case::Snake);
                     Field {
-                        name: (name, span).to_lit_str(),
-                        field_name: (name.to_case(Case::Snake), span).to_ident(),
+                        name: (&name, span).to_lit_str(),
+                        field_name: (field_name, span).to_ident(),
+                        input_keys: vec![],
                         optional: None,
                         typ: None,
+                        readonly: None,
+                        writeonly: None,
+                        example: None,
+                        require: None,
+                        secret: None,
+                        int_or_str: None,
                         alias: None,
                         expr,
+                        condition: None,
+                        json_path: None,
+                        cow: false,
+                        stringify: false,
+                        skip_empty: false,
                         default,
+                        attrs: vec![],
                     }
                 })
                 .collect::<Vec<_>>(),
@@ -180,6 +211,9 @@ pub mod url_parser {
         let (rest, schema) = opt(alt((
             terminated(tag("https"), tag("://")),
             terminated(tag("http"), tag("://")),
+            // websocket endpoints, declared before the upgrade is supported
+            terminated(tag("wss"), tag("://")),
+            terminated(tag("ws"), tag("://")),
         )))(input)?;
 
         let mut auth = None;
@@ -405,6 +439,8 @@ pub mod url_parser {
                 dollar: span,
                 name: (self.name, span).to_ident(),
                 client_option: self.client_option,
+                optional: None,
+                shared: false,
                 typ: self.typ.map(|typ| match typ {
                     "string" => Type::String(StringType { span }),
                     "bool" => Type::Bool(span),
@@ -470,8 +506,11 @@ pub mod url_parser {
                 map(alt((tag("string"), tag("str"), tag("String"))), |_| {
                     "string"
                 }),
-                map(alt((tag("int"), tag("integer"), tag("i64"))), |_| "i64"),
-                map(alt((tag("uint"), tag("u64"))), |_| "u64"),
+                // bare keywords take the client's `default_int_width` later
+                map(alt((tag("integer"), tag("int"))), |_| "int"),
+                map(tag("uint"), |_| "uint"),
+                map(tag("i64"), |_| "i64"),
+                map(tag("u64"), |_| "u64"),
                 map(tag("i8"), |_| "i8"),
                 map(tag("u8"), |_| "u8"),
                 map(tag("i16"), |_| "i16"),
@@ -529,12 +568,47 @@ pub mod url_parser {
         context(
             "query param",
             map(
-                preceded(
-                    tag("&"),
-                    tuple((code_points, opt(preceded(tag("="), path_segment)))),
-                ),
+                tuple((
+                    query_code_points,
+                    opt(preceded(
+                        tag("="),
+                        alt((
+                            map(query_code_points, Segment::CodePoints),
+                            map(variable, Segment::Variable),
+                        )),
+                    )),
+                )),
                 |(name, value)| Param { name, value },
             ),
         )(input)
     }
+
+    // code points and percent-encoded sequences like `%20`
+    fn query_code_points(input: &str) -> IResult<&str, &str> {
+        recognize(many1(alt((
+            take_while1(|item: char| {
+                item.is_alphanum() || item == '-' || item == '.' || item == '_' || item == '~'
+            }),
+            recognize(preceded(
+                tag("%"),
+                take_while_m_n(2, 2, |item: char| item.is_ascii_hexdigit()),
+            )),
+        ))))(input)
+    }
+
+    fn percent_decode(input: &str) -> Option<String> {
+        let mut bytes = vec![];
+        let mut rest = input.as_bytes();
+        while let Some((&b, tail)) = rest.split_first() {
+            if b == b'%' {
+                let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..];
+            } else {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+        String::from_utf8(bytes).ok()
+    }
 }
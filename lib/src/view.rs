@@ -0,0 +1,249 @@
+use quote::ToTokens;
+use serde::Serialize;
+
+use crate::model::*;
+
+// a serializable snapshot of the resolved client model, for build scripts and
+// codegen tools that reuse the parser
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ClientView {
+    pub name: String,
+    pub options: Option<StructView>,
+    pub apis: Vec<ApiView>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ApiView {
+    pub name: String,
+    pub method: String,
+    pub uri: String,
+    pub variables: Vec<VariableView>,
+    pub request: RequestView,
+    pub response: Option<ResponseView>,
+    pub blocking: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VariableView {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: Option<String>,
+    pub client_option: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RequestView {
+    pub header: Option<StructView>,
+    pub query: Option<StructView>,
+    pub data: Option<DataView>,
+    pub data_match: Option<DataMatchView>,
+    pub bytes_body: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DataMatchView {
+    pub var: String,
+    pub enum_name: String,
+    pub arms: Vec<DataArmView>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DataArmView {
+    pub name: String,
+    pub variables: Vec<VariableView>,
+    pub data: StructView,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ResponseView {
+    pub optional: bool,
+    pub text: bool,
+    pub header: Option<StructView>,
+    pub cookie: Option<StructView>,
+    pub data: Option<DataView>,
+    pub page_info: Option<PageInfoView>,
+    pub success_status: Option<Vec<u16>>,
+    pub rate_limit_info: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PageInfoView {
+    pub total: String,
+    pub page: String,
+    pub size: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DataView {
+    pub data_type: String,
+    #[serde(flatten)]
+    pub data: StructView,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct StructView {
+    pub struct_name: String,
+    pub fields: Vec<FieldView>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldView {
+    pub name: String,
+    pub field_name: String,
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub optional: bool,
+    pub readonly: bool,
+    pub writeonly: bool,
+    pub require: bool,
+    pub secret: bool,
+    pub int_or_str: bool,
+    // the bool client option the field is conditioned on
+    pub condition: Option<String>,
+    // `$.data.token` style path of a response field
+    pub json_path: Option<String>,
+    // extra keys accepted when deserializing
+    pub input_keys: Vec<String>,
+}
+
+impl Client {
+    pub fn to_view(&self) -> ClientView {
+        ClientView {
+            name: self.name.to_string(),
+            options: self.options.as_ref().map(|options| options.to_view()),
+            apis: self.apis.iter().map(|api| api.to_view()).collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.to_view())
+    }
+}
+
+impl Api {
+    fn to_view(&self) -> ApiView {
+        ApiView {
+            name: self.name.to_string(),
+            method: self.method.to_string(),
+            uri: self.uri.uri_format.value(),
+            blocking: self.blocking.is_some(),
+            variables: self.variables.iter().map(|var| var.to_view()).collect(),
+            request: RequestView {
+                header: self.request.header.as_ref().map(|h| h.to_view()),
+                query: self.request.query.as_ref().map(|q| q.to_view()),
+                data: self
+                    .request
+                    .data
+                    .as_ref()
+                    .map(|data| data.data_type.to_view(&data.data)),
+                data_match: self
+                    .request
+                    .data
+                    .as_ref()
+                    .and_then(|data| data.data_match.as_ref())
+                    .map(|data_match| data_match.to_view()),
+                bytes_body: self
+                    .request
+                    .bytes_body
+                    .as_ref()
+                    .map(|body| body.var.to_string()),
+            },
+            response: self.response.as_ref().map(|response| ResponseView {
+                optional: response.optional.is_some(),
+                text: response.text.is_some(),
+                header: response.header.as_ref().map(|h| h.to_view()),
+                cookie: response.cookie.as_ref().map(|c| c.to_view()),
+                data: response
+                    .data
+                    .as_ref()
+                    .map(|data| data.data_type.to_view(&data.data)),
+                page_info: response.page_info.as_ref().map(|page_info| PageInfoView {
+                    total: page_info.total.to_string(),
+                    page: page_info.page.to_string(),
+                    size: page_info.size.to_string(),
+                }),
+                rate_limit_info: response.rate_limit_info.is_some(),
+                success_status: response.success_status.as_ref().map(|success_status| {
+                    success_status
+                        .codes
+                        .iter()
+                        .filter_map(|code| code.base10_parse().ok())
+                        .collect()
+                }),
+            }),
+        }
+    }
+}
+
+impl Variable {
+    fn to_view(&self) -> VariableView {
+        VariableView {
+            name: self.name.to_string(),
+            typ: self
+                .typ
+                .as_ref()
+                .map(|typ| typ.to_type().to_token_stream().to_string()),
+            client_option: self.client_option,
+        }
+    }
+}
+
+impl DataMatch {
+    fn to_view(&self) -> DataMatchView {
+        DataMatchView {
+            var: self.var.to_string(),
+            enum_name: self.enum_name.to_string(),
+            arms: self
+                .arms
+                .iter()
+                .map(|arm| DataArmView {
+                    name: arm.name.to_string(),
+                    variables: arm.variables.iter().map(|var| var.to_view()).collect(),
+                    data: arm.data.to_view(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl DataType {
+    fn to_view(&self, data: &BracedConfig) -> DataView {
+        DataView {
+            data_type: match self {
+                Self::Json(_) => "json",
+                Self::Form(_) => "form",
+                Self::Urlencoded(_) => "urlencoded",
+                Self::Multipart(_) => "multipart",
+            }
+            .to_owned(),
+            data: data.to_view(),
+        }
+    }
+}
+
+impl BracedConfig {
+    fn to_view(&self) -> StructView {
+        StructView {
+            struct_name: self.struct_name.to_string(),
+            fields: self
+                .fields
+                .iter()
+                .map(|field| FieldView {
+                    name: field.name.value(),
+                    field_name: field.field_name.to_string(),
+                    typ: field.to_field_type().to_token_stream().to_string(),
+                    optional: field.optional.is_some(),
+                    readonly: field.readonly.is_some(),
+                    writeonly: field.writeonly.is_some(),
+                    require: field.require.is_some(),
+                    secret: field.secret.is_some(),
+                    int_or_str: field.int_or_str.is_some(),
+                    condition: field.condition.as_ref().map(|var| var.name.to_string()),
+                    json_path: field.json_path.as_ref().map(|path| path.to_pointer()),
+                    input_keys: field.input_keys.iter().map(|key| key.value()).collect(),
+                })
+                .collect(),
+        }
+    }
+}
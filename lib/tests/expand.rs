@@ -0,0 +1,23 @@
+use power_reqwest_lib::parse_client;
+use quote::{quote, ToTokens};
+
+fn expand(declaration: proc_macro2::TokenStream) -> String {
+    parse_client(declaration)
+        .unwrap()
+        .to_token_stream()
+        .to_string()
+}
+
+#[test]
+fn allow_dead_code_marks_the_generated_items() {
+    let declaration = |allow: bool| {
+        quote! {
+            name: Unused,
+            allow_dead_code: #allow,
+            get item("http://127.0.0.1/item") -> { json { Id: uint } }
+        }
+    };
+    let allow = quote!(#[allow(dead_code)]).to_string();
+    assert!(expand(declaration(true)).contains(&allow));
+    assert!(!expand(declaration(false)).contains(&allow));
+}
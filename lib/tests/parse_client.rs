@@ -0,0 +1,36 @@
+use power_reqwest_lib::parse_client;
+
+// the declaration inside the `reqwest!` invocation of an example
+fn example_declaration(path: &str) -> proc_macro2::TokenStream {
+    let source = std::fs::read_to_string(path).unwrap();
+    let file = syn::parse_file(&source).unwrap();
+    file.items
+        .into_iter()
+        .find_map(|item| match item {
+            syn::Item::Macro(item) if item.mac.path.is_ident("reqwest") => Some(item.mac.tokens),
+            _ => None,
+        })
+        .expect("the example should invoke reqwest!")
+}
+
+#[test]
+fn the_aliyun_example_parses() {
+    let client = parse_client(example_declaration("../examples/aliyun_sms.rs")).unwrap();
+    assert_eq!(client.name, "AliyunSmsClient");
+    assert_eq!(client.apis.len(), 29);
+    let send_sms = client
+        .apis
+        .iter()
+        .find(|api| api.name == "send_sms")
+        .unwrap();
+    assert_eq!(send_sms.method, "post");
+    assert!(client
+        .option_map
+        .contains_key(&syn::parse_str("ak").unwrap()));
+}
+
+#[test]
+fn a_bad_declaration_is_an_error() {
+    let error = parse_client(quote::quote!(name: lowercase)).unwrap_err();
+    assert_eq!(error.to_string(), "expect 'UpperCamel' case name");
+}
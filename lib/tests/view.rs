@@ -0,0 +1,28 @@
+use power_reqwest_lib::parse_client;
+use quote::quote;
+
+#[test]
+fn every_data_type_has_a_view() {
+    let client = parse_client(quote! {
+        name: Docs,
+        post upload("http://127.0.0.1/docs") {
+            multipart {
+                Title: string,
+                Doc: file("application/pdf"),
+            } = $form
+        }
+        post save("http://127.0.0.1/save") {
+            json { Title: string = $title }
+        }
+    })
+    .unwrap();
+    let view = client.to_view();
+    let data_types = view
+        .apis
+        .iter()
+        .map(|api| api.request.data.as_ref().unwrap().data_type.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(data_types, ["multipart", "json"]);
+    let json: serde_json::Value = serde_json::from_str(&client.to_json().unwrap()).unwrap();
+    assert_eq!(json["apis"][0]["request"]["data"]["data_type"], "multipart");
+}
@@ -0,0 +1,17 @@
+use power_reqwest_lib::parse_client;
+use quote::ToTokens;
+
+#[proc_macro]
+pub fn reqwest(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match parse_client(input.into()) {
+        Ok(client) => {
+            // _ = std::fs::write("examples/x2.text", format!("{:#?}", &client));
+            // _ = std::fs::write("examples/x.rs", client.to_token_stream().to_string());
+            if client.validate_only {
+                return proc_macro::TokenStream::new();
+            }
+            client.to_token_stream().into()
+        }
+        Err(err) => err.to_compile_error().into(),
+    }
+}
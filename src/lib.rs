@@ -1,14 +1,32 @@
-use power_reqwest_lib::Client;
-use quote::ToTokens;
+//! `reqwest!` generates an async http api client from a declaration of its
+//! apis.
+//!
+//! The generated code reaches `reqwest`, `serde`, `serde_json`,
+//! `serde_urlencoded`, `chrono`, `futures-util`, `bytes` and `tokio` through
+//! this crate, the invoking crate needs no other dependency.
+//!
+//! Cargo features, all enabled by default:
+//! - `cookies`: reqwest's cookie store, for the `cookies: true` client option
+//! - `multipart`: reqwest's multipart forms, for `multipart` request bodies.
+//!   A `file` field takes a `(file name, bytes)` pair, `file("image/png")`
+//!   also sets the part's mime type
+//! - `decompress`: gzip, brotli and deflate responses, decoded by reqwest or,
+//!   with the `decompress: manual` client option, by the generated code
 
-#[proc_macro]
-pub fn reqwest(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    match syn::parse::<Client>(input) {
-        Ok(client) => {
-            // _ = std::fs::write("examples/x2.text", format!("{:#?}", &client));
-            // _ = std::fs::write("examples/x.rs", client.to_token_stream().to_string());
-            client.to_token_stream().into()
-        }
-        Err(err) => err.to_compile_error().into(),
-    }
+pub use power_reqwest_macros::reqwest;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "decompress")]
+    pub use brotli_decompressor;
+    pub use bytes;
+    pub use chrono;
+    #[cfg(feature = "decompress")]
+    pub use flate2;
+    pub use futures_util;
+    pub use reqwest;
+    pub use serde;
+    pub use serde_json;
+    pub use serde_urlencoded;
+    pub use tokio;
 }
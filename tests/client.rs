@@ -0,0 +1,717 @@
+// client options and the generated client
+mod common;
+
+mod options_builder {
+    use super::common;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Pinger,
+        params: { port?: u16, host?: String = "127.0.0.1", token?: String },
+        get ping("http://$$host:$$port/ping?token=$$token")
+    }
+
+    #[tokio::test]
+    async fn builder_fills_the_options() {
+        let server = MockServer::start(vec![common::ok()]);
+        let options = PingerOptions::builder().port(server.port).token("abc");
+        let client = Pinger::new(options);
+        client.ping().await.unwrap();
+        assert_eq!(server.request().target, "/ping?token=abc");
+    }
+
+    #[test]
+    fn new_default_takes_no_options() {
+        // every option is optional, so the client needs no arguments
+        let _client = Pinger::new_default();
+    }
+}
+
+mod request_key {
+    use super::common;
+    use power_reqwest::reqwest;
+    use std::collections::HashMap;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16 },
+        post send_sms("http://127.0.0.1:$$port/send") {
+            json {
+                PhoneNumber: string = $phone_number,
+                TemplateCode: string = $template_code,
+                Sign: string = $sign,
+            }
+            key: [phone_number, template_code]
+        }
+    }
+
+    #[tokio::test]
+    async fn key_covers_only_the_key_fields() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Sms::new(SmsOptions { port })
+        });
+
+        let phone = "13800138000".to_owned();
+        let template = "T1".to_owned();
+        let key = client.send_sms_key(&phone, &template);
+        assert_eq!(key, r#"send_sms:["13800138000","T1"]"#);
+
+        // calls that differ only in the sign share the key
+        for sign in ["a", "b"] {
+            client
+                .send_sms(phone.clone(), template.clone(), sign)
+                .await
+                .unwrap();
+            assert_eq!(server.request().json()["Sign"], sign);
+            assert_eq!(client.send_sms_key(&phone, &template), key);
+        }
+        assert_ne!(client.send_sms_key(&phone, &"T2".to_owned()), key);
+    }
+
+    #[tokio::test]
+    async fn calls_differing_in_other_fields_share_a_cache_entry() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Sms::new(SmsOptions { port }));
+        let mut cache = HashMap::new();
+        let phone = "13800138000".to_owned();
+        let template = "T1".to_owned();
+        for sign in ["a", "b"] {
+            let key = client.send_sms_key(&phone, &template);
+            if !cache.contains_key(&key) {
+                client
+                    .send_sms(phone.clone(), template.clone(), sign)
+                    .await
+                    .unwrap();
+                cache.insert(key, sign);
+            }
+        }
+        assert_eq!(cache.into_values().collect::<Vec<_>>(), ["a"]);
+        assert_eq!(server.request().json()["Sign"], "a");
+        assert!(server.no_more_requests());
+    }
+}
+
+mod type_affix {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        type_prefix: Ali,
+        type_suffix: Type,
+        params: { port: u16 },
+        post send("http://127.0.0.1:$$port/send") {
+            json {
+                Phone: string,
+                Extra { Sign: string },
+            } = $message
+        } -> {
+            json { Code: string }
+        }
+    }
+
+    #[tokio::test]
+    async fn generated_types_carry_the_affixes() {
+        let (server, client) = common::serve(vec![common::json(r#"{"Code":"OK"}"#)], |port| {
+            Sms::new(AliSmsOptionsType { port })
+        });
+        let response: AliSendResponseDataType = client
+            .send(AliSendRequestDataType {
+                phone: "123".to_owned(),
+                extra: AliSendRequestDataExtraType {
+                    sign: "shop".to_owned(),
+                },
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.code, "OK");
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Phone": "123", "Extra": { "Sign": "shop" } })
+        );
+    }
+}
+
+mod on_submit {
+    use super::common;
+    use power_reqwest::reqwest;
+    use std::sync::Mutex;
+
+    reqwest! {
+        name: Sign,
+        params: { port: u16 },
+        canonical_headers: sorted,
+        hooks: { on_submit: sign },
+        get report("http://127.0.0.1:$$port/report") {
+            accept: "text/csv",
+            auth: basic($user, $pass),
+            header {
+                Zeta = $zeta,
+                Alpha = "a",
+            }
+            query {
+                B: string = $b,
+                A: "1",
+            }
+        }
+    }
+
+    // what the hook saw: header names and the canonical query
+    static SEEN: Mutex<Option<(Vec<String>, String)>> = Mutex::new(None);
+
+    fn sign(mut request: ::reqwest::Request) -> Result<::reqwest::Request, SignError> {
+        let names = request
+            .headers()
+            .keys()
+            .map(|name| name.to_string())
+            .collect();
+        let params = request
+            .url()
+            .query_pairs()
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect::<Vec<_>>();
+        let canonical = Sign::canonical_query(&params);
+        request
+            .headers_mut()
+            .insert("x-signature", canonical.parse().unwrap());
+        *SEEN.lock().unwrap() = Some((names, canonical));
+        Ok(request)
+    }
+
+    #[tokio::test]
+    async fn the_hook_signs_sorted_headers_and_the_canonical_query() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Sign::new(SignOptions { port }));
+        client.report("z", "x y", "ann", "secret").await.unwrap();
+        let (names, canonical) = SEEN.lock().unwrap().take().unwrap();
+        assert_eq!(names, ["accept", "alpha", "authorization", "zeta"]);
+        assert_eq!(canonical, "A=1&B=x%20y");
+        let request = server.request();
+        assert_eq!(request.header("x-signature"), Some("A=1&B=x%20y"));
+        let sent = request
+            .headers
+            .iter()
+            .map(|(name, _)| name.to_lowercase())
+            .filter(|name| names.contains(name))
+            .collect::<Vec<_>>();
+        assert_eq!(sent, names);
+    }
+
+    #[test]
+    fn canonical_query_of_fixed_params() {
+        let params = [
+            ("Timestamp", "2024-01-01T00:00:00Z"),
+            ("Action", "SendSms"),
+            ("PhoneNumbers", "1 2*3~"),
+        ]
+        .map(|(key, value)| (key.to_owned(), value.to_owned()));
+        assert_eq!(
+            Sign::canonical_query(&params),
+            "Action=SendSms&PhoneNumbers=1%202%2A3~&Timestamp=2024-01-01T00%3A00%3A00Z"
+        );
+    }
+}
+
+mod verbatim_method_names {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16 },
+        method_case: verbatim,
+        post AddSmsSign("http://127.0.0.1:$$port/") {
+            json { SignName: string = $sign_name }
+        }
+    }
+
+    #[tokio::test]
+    async fn methods_keep_their_declared_name() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Sms::new(SmsOptions { port }));
+        client.AddSmsSign("shop").await.unwrap();
+        assert_eq!(server.request().json()["SignName"], "shop");
+    }
+}
+
+mod shared_error_type {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    #[derive(Debug)]
+    pub enum ApiError {
+        Transport,
+        Status(u16),
+        Other,
+    }
+
+    impl From<::reqwest::Error> for ApiError {
+        fn from(_: ::reqwest::Error) -> Self {
+            Self::Transport
+        }
+    }
+
+    impl From<UsersError> for ApiError {
+        fn from(err: UsersError) -> Self {
+            match err {
+                UsersError::Status { status, .. } => Self::Status(status.as_u16()),
+                _ => Self::Other,
+            }
+        }
+    }
+
+    impl From<OrdersError> for ApiError {
+        fn from(err: OrdersError) -> Self {
+            match err {
+                OrdersError::Status { status, .. } => Self::Status(status.as_u16()),
+                _ => Self::Other,
+            }
+        }
+    }
+
+    reqwest! {
+        name: Users,
+        params: { port: u16 },
+        error_type: ApiError,
+        get user("http://127.0.0.1:$$port/user")
+    }
+
+    reqwest! {
+        name: Orders,
+        params: { port: u16 },
+        error_type: ApiError,
+        get order("http://127.0.0.1:$$port/order")
+    }
+
+    #[tokio::test]
+    async fn both_clients_return_the_shared_error() {
+        let not_found = || common::response("404 Not Found", &[], "");
+        let (_server, users) =
+            common::serve(vec![not_found()], |port| Users::new(UsersOptions { port }));
+        let (_server, orders) = common::serve(vec![not_found()], |port| {
+            Orders::new(OrdersOptions { port })
+        });
+        let errors: [ApiError; 2] = [
+            users.user().await.unwrap_err(),
+            orders.order().await.unwrap_err(),
+        ];
+        assert!(matches!(
+            errors,
+            [ApiError::Status(404), ApiError::Status(404)]
+        ));
+    }
+}
+
+mod string_args {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Greeter,
+        params: { port: u16 },
+        get greet("http://127.0.0.1:$$port/greet") {
+            query { Name: string = $name }
+        }
+    }
+
+    #[tokio::test]
+    async fn string_args_take_str_literals() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Greeter::new(GreeterOptions { port })
+        });
+        client.greet("ann").await.unwrap();
+        assert_eq!(server.request().target, "/greet?Name=ann");
+        client.greet(String::from("bob")).await.unwrap();
+        assert_eq!(server.request().target, "/greet?Name=bob");
+    }
+}
+
+mod validate_only {
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Ghost,
+        validate_only: true,
+        get item("http://127.0.0.1/item") -> { json { Id: uint } }
+    }
+
+    // would collide with the generated items if any were emitted
+    struct Ghost;
+    struct GhostOptions;
+    struct GhostError;
+    struct ItemResponseData;
+
+    #[test]
+    fn valid_declarations_emit_nothing() {
+        let _ = (Ghost, GhostOptions, GhostError, ItemResponseData);
+    }
+}
+
+mod blocking {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Setup,
+        params: { port: u16 },
+        blocking get config("http://127.0.0.1:$$port/config") -> {
+            json { Region: string }
+        }
+    }
+
+    #[test]
+    fn blocking_calls_share_a_runtime() {
+        let (server, client) = common::serve(
+            vec![
+                common::json(r#"{"Region":"eu"}"#),
+                common::json(r#"{"Region":"us"}"#),
+            ],
+            |port| Setup::new(SetupOptions { port }),
+        );
+        assert_eq!(client.config_blocking().unwrap().region, "eu");
+        assert_eq!(server.request().target, "/config");
+        assert_eq!(client.config_blocking().unwrap().region, "us");
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Cannot start a runtime from within a runtime")]
+    async fn blocking_calls_panic_in_async_code() {
+        let (_server, client) = common::serve(vec![], |port| Setup::new(SetupOptions { port }));
+        let _ = client.config_blocking();
+    }
+}
+
+mod default_int_width {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Narrow,
+        params: { port: u16 },
+        default_int_width: 32,
+        get list("http://127.0.0.1:$$port/list") {
+            query { Offset: int = $offset, Size: u64 = $size }
+        } -> {
+            json { Count: uint, Ids: uint[] }
+        }
+    }
+
+    reqwest! {
+        name: Wide,
+        params: { port: u16 },
+        get total("http://127.0.0.1:$$port/total") -> {
+            json { Count: uint }
+        }
+    }
+
+    #[tokio::test]
+    async fn bare_ints_follow_the_width() {
+        let (server, client) =
+            common::serve(vec![common::json(r#"{"Count":2,"Ids":[7,8]}"#)], |port| {
+                Narrow::new(NarrowOptions { port })
+            });
+        let offset: i32 = -1;
+        // explicit widths are kept
+        let size: u64 = 10;
+        let data = client.list(offset, size).await.unwrap();
+        let count: u32 = data.count;
+        let ids: Vec<u32> = data.ids;
+        assert_eq!((count, ids), (2, vec![7, 8]));
+        assert_eq!(server.request().target, "/list?Offset=-1&Size=10");
+
+        let (_server, client) =
+            common::serve(vec![common::json(r#"{"Count":5000000000}"#)], |port| {
+                Wide::new(WideOptions { port })
+            });
+        let count: u64 = client.total().await.unwrap().count;
+        assert_eq!(count, 5_000_000_000);
+    }
+}
+
+mod retry_after {
+    use super::common;
+    use ::reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+    use power_reqwest::reqwest;
+    use std::time::{Duration, Instant};
+
+    reqwest! {
+        name: Flaky,
+        params: { port: u16 },
+        retry: { attempts: 2, delay: "30s", status: [429, 503] },
+        get ping("http://127.0.0.1:$$port/ping")
+    }
+
+    fn headers(value: &str) -> HeaderMap {
+        HeaderMap::from_iter([(RETRY_AFTER, HeaderValue::from_str(value).unwrap())])
+    }
+
+    #[test]
+    fn delta_seconds() {
+        assert_eq!(
+            Flaky::retry_after(&headers("120")),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(Flaky::retry_after(&headers(" 0 ")), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn http_dates() {
+        let at = chrono::Utc::now() + chrono::Duration::seconds(90);
+        let delay = Flaky::retry_after(&headers(&at.to_rfc2822())).unwrap();
+        assert!(delay > Duration::from_secs(80) && delay <= Duration::from_secs(90));
+        let at = (chrono::Utc::now() + chrono::Duration::hours(1))
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        let delay = Flaky::retry_after(&headers(&at)).unwrap();
+        assert!(delay > Duration::from_secs(3500));
+        // already passed
+        assert_eq!(
+            Flaky::retry_after(&headers("Sun, 06 Nov 1994 08:49:37 GMT")),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_ignored() {
+        assert_eq!(Flaky::retry_after(&HeaderMap::new()), None);
+        assert_eq!(Flaky::retry_after(&headers("-1")), None);
+        assert_eq!(Flaky::retry_after(&headers("soon")), None);
+    }
+
+    #[tokio::test]
+    async fn retry_after_replaces_the_delay() {
+        let (server, client) = common::serve(
+            vec![
+                common::response("503 Service Unavailable", &[("retry-after", "1")], ""),
+                common::ok(),
+                common::response(
+                    "429 Too Many Requests",
+                    &[("retry-after", "Sun, 06 Nov 1994 08:49:37 GMT")],
+                    "",
+                ),
+                common::ok(),
+            ],
+            |port| Flaky::new(FlakyOptions { port }),
+        );
+        let started = Instant::now();
+        client.ping().await.unwrap();
+        let elapsed = started.elapsed();
+        assert!(elapsed >= Duration::from_secs(1) && elapsed < Duration::from_secs(10));
+        let started = Instant::now();
+        client.ping().await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
+        for _ in 0..4 {
+            assert_eq!(server.request().target, "/ping");
+        }
+    }
+}
+
+mod base_urls {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    // nothing listens on port 1
+    reqwest! {
+        name: Mirrors,
+        base_urls: ["http://127.0.0.1:1", "http://127.0.0.1:47391"],
+        get file("/files/$name")
+    }
+
+    #[tokio::test]
+    async fn the_next_base_url_is_tried_when_connecting_fails() {
+        let server = common::MockServer::start_on(47391, vec![common::ok()]);
+        Mirrors::new().file("a.txt").await.unwrap();
+        assert_eq!(server.request().target, "/files/a.txt");
+    }
+}
+
+mod debug_requests {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Login,
+        params: { port: u16 },
+        debug: true,
+        post login("http://127.0.0.1:$$port/login") {
+            query {
+                Key: string secret = $key,
+                Page: "1",
+            }
+            json {
+                User: string = $user,
+                Password: string secret = $password,
+            }
+        }
+    }
+
+    // run in a child process by the test below, which reads its stderr
+    #[tokio::test]
+    #[ignore]
+    async fn print_a_request() {
+        let (_server, client) =
+            common::serve(vec![common::ok()], |port| Login::new(LoginOptions { port }));
+        client.login("k1", "ann", "hunter2").await.unwrap();
+    }
+
+    #[test]
+    fn printed_requests_mask_secrets() {
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["debug_requests::print_a_request", "--exact", "--ignored"])
+            .args(["--nocapture", "--test-threads=1"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let printed = String::from_utf8(output.stderr).unwrap();
+        assert!(printed.contains("/login?Key=***&Page=1"), "{printed}");
+        assert!(printed.contains(r#""Password":"***""#), "{printed}");
+        assert!(printed.contains(r#""User":"ann""#), "{printed}");
+        assert!(!printed.contains("hunter2") && !printed.contains("k1"));
+    }
+}
+
+mod list_setters {
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Scoped,
+        params: { scopes: string[], categories?: uint[] },
+        get ping("http://127.0.0.1/ping")
+    }
+
+    #[test]
+    fn lists_are_built_one_element_at_a_time() {
+        let options = ScopedOptions::builder()
+            .add_scope("read")
+            .add_scope(String::from("write"))
+            .add_category(1)
+            .add_category(2);
+        assert_eq!(options.scopes, ["read", "write"]);
+        assert_eq!(options.categories, Some(vec![1, 2]));
+        let options = options.scopes(["admin".to_owned()]).add_scope("audit");
+        assert_eq!(options.scopes, ["admin", "audit"]);
+    }
+}
+
+mod separators {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    // semicolons and commas, trailing ones included, are accepted alike
+    reqwest! {
+        name: Semi;
+        params: { port: u16; };
+        post save("http://127.0.0.1:$$port/save") {
+            query { Mode: "fast"; Page: uint = $page; };
+            json {
+                Name: string = $name;
+                Owner { Id: uint; Tags: string[]; } = $owner,
+            };
+        } -> {
+            json { Id: uint; Ok: bool, };
+        };
+    }
+
+    #[tokio::test]
+    async fn semicolon_separated_fields() {
+        let (server, client) = common::serve(vec![common::json(r#"{"Id":7,"Ok":true}"#)], |port| {
+            Semi::new(SemiOptions { port })
+        });
+        let owner = SaveRequestDataOwner {
+            id: 1,
+            tags: vec!["a".to_owned()],
+        };
+        let data = client.save(2, "x", owner).await.unwrap();
+        assert_eq!((data.id, data.ok), (7, true));
+        let request = server.request();
+        assert_eq!(request.target, "/save?Mode=fast&Page=2");
+        assert_eq!(
+            request.json(),
+            serde_json::json!({ "Name": "x", "Owner": { "Id": 1, "Tags": ["a"] } })
+        );
+    }
+}
+
+mod module {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        module: sms_api,
+        params: { port: u16 },
+        hooks: { on_submit: stamp },
+        post send("http://127.0.0.1:$$port/send") {
+            json { Phone: string = $phone }
+        } -> {
+            json { Code: string }
+        }
+    }
+
+    // defined next to the invocation, reached from inside the module
+    fn stamp(mut request: ::reqwest::Request) -> Result<::reqwest::Request, sms_api::SmsError> {
+        request
+            .headers_mut()
+            .insert("x-stamp", "1".parse().unwrap());
+        Ok(request)
+    }
+
+    #[tokio::test]
+    async fn items_live_under_the_module() {
+        let (server, client) = common::serve(vec![common::json(r#"{"Code":"OK"}"#)], |port| {
+            sms_api::Sms::new(sms_api::SmsOptions { port })
+        });
+        let data: sms_api::SendResponseData = client.send("1").await.unwrap();
+        assert_eq!(data.code, "OK");
+        assert_eq!(server.request().header("x-stamp"), Some("1"));
+    }
+}
+
+mod cookies {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Session,
+        params: { port: u16 },
+        cookies: true,
+        post login("http://127.0.0.1:$$port/login")
+        get me("http://127.0.0.1:$$port/me")
+    }
+
+    reqwest! {
+        name: Stateless,
+        params: { port: u16 },
+        post login("http://127.0.0.1:$$port/login")
+        get me("http://127.0.0.1:$$port/me")
+    }
+
+    fn responses() -> Vec<Vec<u8>> {
+        vec![
+            common::response("200 OK", &[("set-cookie", "sid=abc; Path=/")], ""),
+            common::ok(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn a_cookie_set_by_one_call_is_sent_on_the_next() {
+        let (server, client) =
+            common::serve(responses(), |port| Session::new(SessionOptions { port }));
+        client.login().await.unwrap();
+        assert_eq!(server.request().header("cookie"), None);
+        client.me().await.unwrap();
+        assert_eq!(server.request().header("cookie"), Some("sid=abc"));
+    }
+
+    #[tokio::test]
+    async fn cookies_are_dropped_by_default() {
+        let (server, client) = common::serve(responses(), |port| {
+            Stateless::new(StatelessOptions { port })
+        });
+        client.login().await.unwrap();
+        client.me().await.unwrap();
+        server.request();
+        assert_eq!(server.request().header("cookie"), None);
+    }
+}
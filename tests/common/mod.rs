@@ -0,0 +1,188 @@
+// shared by the integration tests, not every test uses every helper
+#![allow(dead_code)]
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+// a request as the mock server read it off the wire
+#[derive(Debug)]
+pub struct Recorded {
+    pub method: String,
+    // the path and query, as sent
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    // chunked bodies are joined
+    pub body: Vec<u8>,
+    pub chunked: bool,
+}
+
+impl Recorded {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn header_values(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    pub fn body_text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    pub fn json(&self) -> serde_json::Value {
+        serde_json::from_slice(&self.body).expect("request body should be json")
+    }
+}
+
+// answers one connection per canned response, in order, and hands the
+// requests it read to the test
+pub struct MockServer {
+    pub port: u16,
+    requests: mpsc::Receiver<Recorded>,
+}
+
+impl MockServer {
+    pub fn start(responses: Vec<Vec<u8>>) -> Self {
+        Self::start_on(0, responses)
+    }
+
+    // on a fixed port, for urls written into a declaration
+    pub fn start_on(port: u16, responses: Vec<Vec<u8>>) -> Self {
+        let listener = TcpListener::bind(("127.0.0.1", port)).expect("bind a local port");
+        let port = listener.local_addr().unwrap().port();
+        let (sender, requests) = mpsc::channel();
+        thread::spawn(move || {
+            for response in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+                let Some(request) = read_request(&mut reader) else {
+                    return;
+                };
+                let mut stream = reader.into_inner();
+                _ = stream.write_all(&response);
+                _ = stream.flush();
+                if sender.send(request).is_err() {
+                    return;
+                }
+            }
+        });
+        Self { port, requests }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://127.0.0.1:{}{path}", self.port)
+    }
+
+    pub fn request(&self) -> Recorded {
+        self.requests
+            .recv_timeout(Duration::from_secs(5))
+            .expect("the server should get a request")
+    }
+
+    pub fn no_more_requests(&self) -> bool {
+        self.requests
+            .recv_timeout(Duration::from_millis(100))
+            .is_err()
+    }
+}
+
+// starts a server with the canned responses and a client reaching it
+pub fn serve<C>(responses: Vec<Vec<u8>>, client: impl FnOnce(u16) -> C) -> (MockServer, C) {
+    let server = MockServer::start(responses);
+    let client = client(server.port);
+    (server, client)
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<Recorded> {
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_owned();
+    let target = parts.next()?.to_owned();
+    let mut headers = vec![];
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let (key, value) = line.split_once(':')?;
+        headers.push((key.trim().to_owned(), value.trim().to_owned()));
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    };
+    let chunked = header("transfer-encoding").is_some_and(|value| value.contains("chunked"));
+    let mut body = vec![];
+    if chunked {
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size).ok()?;
+            let size = usize::from_str_radix(size.trim(), 16).ok()?;
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).ok()?;
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    } else if let Some(length) = header("content-length") {
+        body.resize(length.parse().ok()?, 0);
+        reader.read_exact(&mut body).ok()?;
+    }
+    Some(Recorded {
+        method,
+        target,
+        headers,
+        body,
+        chunked,
+    })
+}
+
+// a complete response, the connection is closed after it
+pub fn response(status: &str, headers: &[(&str, &str)], body: &str) -> Vec<u8> {
+    binary(status, headers, body.as_bytes())
+}
+
+// the same with a body that isn't text, e.g. compressed
+pub fn binary(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut response = format!("HTTP/1.1 {status}\r\nconnection: close\r\n");
+    for (key, value) in headers {
+        response.push_str(&format!("{key}: {value}\r\n"));
+    }
+    response.push_str(&format!("content-length: {}\r\n\r\n", body.len()));
+    let mut response = response.into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+pub fn json(body: &str) -> Vec<u8> {
+    response("200 OK", &[("content-type", "application/json")], body)
+}
+
+pub fn ok() -> Vec<u8> {
+    response("200 OK", &[], "")
+}
+
+// sent as written, for framing that `response` doesn't produce
+pub fn raw(response: &str) -> Vec<u8> {
+    response.as_bytes().to_vec()
+}
@@ -0,0 +1,6 @@
+// the messages of declarations the macro rejects
+#[test]
+fn compile_fail() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,152 @@
+// paginated apis
+mod common;
+
+mod page_info {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Shop,
+        params: { port: u16 },
+        get orders("http://127.0.0.1:$$port/orders?page=$page") -> {
+            json {
+                TotalCount: uint,
+                CurrentPage: uint,
+                PageSize?: uint,
+            }
+            page_info { total: TotalCount, page: CurrentPage, size: PageSize }
+        }
+    }
+
+    #[tokio::test]
+    async fn has_next_page_follows_the_response() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"TotalCount":25,"CurrentPage":2,"PageSize":10}"#),
+                common::json(r#"{"TotalCount":25,"CurrentPage":3,"PageSize":10}"#),
+                common::json(r#"{"TotalCount":25,"CurrentPage":1}"#),
+            ],
+            |port| Shop::new(ShopOptions { port }),
+        );
+
+        let info = client.orders("2").await.unwrap().page_info();
+        assert_eq!(
+            info,
+            ShopPageInfo {
+                total: 25,
+                page: 2,
+                size: 10
+            }
+        );
+        assert_eq!(info.total_pages(), 3);
+        assert!(info.has_next_page());
+
+        let info = client.orders("3").await.unwrap().page_info();
+        assert!(!info.has_next_page());
+
+        // a missing page size counts as zero
+        let info = client.orders("1").await.unwrap().page_info();
+        assert_eq!(info.size, 0);
+        assert!(!info.has_next_page());
+    }
+}
+
+mod page_stream {
+    use super::common;
+    use futures_util::StreamExt;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Shop,
+        params: { port: u16 },
+        get orders("http://127.0.0.1:$$port/orders") {
+            query {
+                Status: string = $status,
+                Page: uint = $page,
+            }
+        } -> {
+            json {
+                Total: uint,
+                Page: uint,
+                Size: uint,
+                Orders: { Id: uint }[],
+            }
+            page_info { total: Total, page: Page, size: Size, items: Orders, next: $page }
+        }
+    }
+
+    #[tokio::test]
+    async fn items_of_every_page_are_streamed() {
+        let (server, client) = common::serve(
+            vec![
+                common::json(r#"{"Total":3,"Page":1,"Size":2,"Orders":[{"Id":1},{"Id":2}]}"#),
+                common::json(r#"{"Total":3,"Page":2,"Size":2,"Orders":[{"Id":3}]}"#),
+            ],
+            |port| Shop::new(ShopOptions { port }),
+        );
+        let ids = client
+            .orders_stream("paid", 1)
+            .map(|order| order.unwrap().id)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(ids, [1, 2, 3]);
+        assert_eq!(server.request().target, "/orders?Status=paid&Page=1");
+        assert_eq!(server.request().target, "/orders?Status=paid&Page=2");
+        assert!(server.no_more_requests());
+    }
+
+    #[tokio::test]
+    async fn a_failed_page_ends_the_stream() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Total":3,"Page":1,"Size":2,"Orders":[{"Id":1},{"Id":2}]}"#),
+                common::response("500 Internal Server Error", &[], ""),
+            ],
+            |port| Shop::new(ShopOptions { port }),
+        );
+        let results = client.orders_stream("paid", 1).collect::<Vec<_>>().await;
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[2], Err(ShopError::Status { .. })));
+    }
+}
+
+mod page_stream_arc {
+    use super::common;
+    use futures_util::StreamExt;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Shop,
+        params: { port: u16 },
+        response_arc: true,
+        get orders("http://127.0.0.1:$$port/orders?page=${page: u64}") -> {
+            json {
+                Total: uint,
+                Page: uint,
+                Size: uint,
+                Orders: { Id: uint }[],
+            }
+            page_info { total: Total, page: Page, size: Size, items: Orders, next: $page }
+        }
+    }
+
+    #[tokio::test]
+    async fn items_are_streamed_out_of_the_arc() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Total":3,"Page":1,"Size":2,"Orders":[{"Id":1},{"Id":2}]}"#),
+                common::json(r#"{"Total":3,"Page":2,"Size":2,"Orders":[{"Id":3}]}"#),
+            ],
+            |port| Shop::new(ShopOptions { port }),
+        );
+        let orders: Vec<OrdersResponseDataOrders> = client
+            .orders_stream(1)
+            .map(|order| order.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            orders.iter().map(|order| order.id).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+}
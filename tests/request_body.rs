@@ -0,0 +1,985 @@
+// request bodies: json, forms, multipart and raw bytes, and the fields in them
+mod common;
+
+mod array_defaults {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Tagger,
+        params: { port: u16 },
+        post tag("http://127.0.0.1:$$port/tag") {
+            json {
+                Name: string = $name,
+                Tags: string[] = ["a", "b"],
+                Weights: int[] = [-1, 2],
+            }
+        } -> {
+            json {
+                Id: uint,
+                Labels: string[] = ["new", "open"],
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn array_constants_are_sent_and_defaulted() {
+        let (server, client) = common::serve(
+            vec![
+                common::json(r#"{"Id":1}"#),
+                common::json(r#"{"Id":2,"Labels":["closed"]}"#),
+            ],
+            |port| Tagger::new(TaggerOptions { port }),
+        );
+
+        let result = client.tag("pen").await.unwrap();
+        assert_eq!(result.id, 1);
+        assert_eq!(result.labels, vec!["new", "open"]);
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Name": "pen", "Tags": ["a", "b"], "Weights": [-1, 2] })
+        );
+
+        let result = client.tag("ink").await.unwrap();
+        assert_eq!(result.labels, vec!["closed"]);
+    }
+}
+
+mod bytes_body {
+    use super::common;
+    use bytes::Bytes;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Store,
+        params: { port: u16 },
+        put object("http://127.0.0.1:$$port/objects/$key") {
+            header {
+                "Content-Type" = "application/octet-stream",
+            }
+            bytes = $payload
+        }
+    }
+
+    #[tokio::test]
+    async fn bytes_are_sent_as_is() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Store::new(StoreOptions { port }));
+        let payload = Bytes::from(vec![0u8, 159, 146, 150, 255]);
+        client.object("a.bin", payload.clone()).await.unwrap();
+        let request = server.request();
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.target, "/objects/a.bin");
+        assert_eq!(
+            request.header("content-type"),
+            Some("application/octet-stream")
+        );
+        assert_eq!(request.header_values("content-length"), ["5"]);
+        assert_eq!(request.body, payload);
+    }
+}
+
+mod conditional_fields {
+    use super::common;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16, region_id: String, is_intl?: bool },
+        post send("http://127.0.0.1:$$port/send") {
+            json {
+                Phone: string = $phone,
+                RegionId: string = $$region_id if $$is_intl,
+            }
+        }
+    }
+
+    fn client(server: &MockServer, is_intl: Option<bool>) -> Sms {
+        Sms::new(SmsOptions {
+            port: server.port,
+            region_id: "ap-southeast-1".to_owned(),
+            is_intl,
+        })
+    }
+
+    #[tokio::test]
+    async fn field_follows_the_option() {
+        let server = MockServer::start(vec![common::ok(), common::ok(), common::ok()]);
+
+        client(&server, Some(true)).send("1").await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Phone": "1", "RegionId": "ap-southeast-1" })
+        );
+
+        client(&server, Some(false)).send("2").await.unwrap();
+        assert_eq!(server.request().json(), serde_json::json!({ "Phone": "2" }));
+
+        client(&server, None).send("3").await.unwrap();
+        assert_eq!(server.request().json(), serde_json::json!({ "Phone": "3" }));
+    }
+}
+
+mod cow_strings {
+    use super::common;
+    use power_reqwest::reqwest;
+    use std::borrow::Cow;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16 },
+        string_type: cow,
+        post send("http://127.0.0.1:$$port/send") {
+            json {
+                Phone: string,
+                Sign: "acme",
+                Extra { Note: string },
+            } = $data
+        } -> {
+            json {
+                Code: string,
+                Message: string = "none",
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn cow_fields_serialize_and_decode() {
+        let (server, client) = common::serve(vec![common::json(r#"{"Code":"OK"}"#)], |port| {
+            Sms::new(SmsOptions { port })
+        });
+        let data = SendRequestData {
+            phone: Cow::Borrowed("1"),
+            sign: Cow::Borrowed(""),
+            extra: SendRequestDataExtra {
+                note: Cow::Owned("hi".to_owned()),
+            },
+        };
+        let result = client.send(data).await.unwrap();
+        let code: Cow<'static, str> = result.code;
+        assert_eq!(code, "OK");
+        assert_eq!(result.message, "none");
+        // the constant is filled in by the method
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Phone": "1", "Sign": "acme", "Extra": { "Note": "hi" } })
+        );
+    }
+}
+
+mod field_examples {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16 },
+        post send("http://127.0.0.1:$$port/send") {
+            json {
+                Phone: string example = "13800138000",
+                Count: uint example = 2,
+                Sign: string,
+            } = $data
+        }
+    }
+
+    #[tokio::test]
+    async fn example_fills_the_declared_values() {
+        let data = SendRequestData::example();
+        assert_eq!(data.phone, "13800138000");
+        assert_eq!(data.count, 2);
+        assert_eq!(data.sign, "");
+
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Sms::new(SmsOptions { port }));
+        client.send(data).await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Phone": "13800138000", "Count": 2, "Sign": "" })
+        );
+    }
+}
+
+mod field_key_refs {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Users,
+        params: { port: u16 },
+        post create("http://127.0.0.1:$$port/users") {
+            json {
+                Name: string = $name,
+                DisplayName: string = $Name,
+                Label: string = $DisplayName,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn field_defaults_from_another_field() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Users::new(UsersOptions { port }));
+        // `$Name` and `$DisplayName` add no arguments of their own
+        client.create("ann").await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Name": "ann", "DisplayName": "ann", "Label": "ann" })
+        );
+    }
+}
+
+mod format_value {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16 },
+        post send("http://127.0.0.1:$$port/send") {
+            json {
+                Text: string = format("{}: {1} of {0}", $total: uint, $name),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn formatted_value_is_sent() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Sms::new(SmsOptions { port }));
+        client.send(3, "pen").await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Text": "3: pen of 3" })
+        );
+    }
+}
+
+mod match_body {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Notify,
+        params: { port: u16 },
+        post send("http://127.0.0.1:$$port/send") {
+            json match $kind {
+                Sms => {
+                    Phone: string = $phone,
+                    Channel: "sms",
+                },
+                Mail => {
+                    Address: string = $address,
+                    Subject: string = $subject,
+                },
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn each_arm_sends_its_shape() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Notify::new(NotifyOptions { port })
+        });
+
+        client
+            .send(SendKind::Sms {
+                phone: "13800138000".to_owned(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Phone": "13800138000", "Channel": "sms" })
+        );
+
+        client
+            .send(SendKind::Mail {
+                address: "a@b.c".to_owned(),
+                subject: "hi".to_owned(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Address": "a@b.c", "Subject": "hi" })
+        );
+    }
+}
+
+mod multipart {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Docs,
+        params: { port: u16 },
+        post upload("http://127.0.0.1:$$port/docs") {
+            multipart {
+                Title: string,
+                Doc: file("application/pdf"),
+                Attachments?: file[],
+            } = $form
+        }
+    }
+
+    #[tokio::test]
+    async fn files_carry_their_name_and_mime_type() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Docs::new(DocsOptions { port }));
+        client
+            .upload(UploadRequestData {
+                title: "report".to_owned(),
+                doc: ("report.pdf".to_owned(), b"%PDF-1.7".to_vec()),
+                attachments: Some(vec![("notes.txt".to_owned(), b"notes".to_vec())]),
+            })
+            .await
+            .unwrap();
+        let request = server.request();
+        assert!(request
+            .header("content-type")
+            .unwrap()
+            .starts_with("multipart/form-data; boundary="));
+        let body = request.body_text();
+        assert!(body.contains("Content-Disposition: form-data; name=\"Title\"\r\n\r\nreport\r\n"));
+        assert!(body.contains(
+            "Content-Disposition: form-data; name=\"Doc\"; filename=\"report.pdf\"\r\n\
+             Content-Type: application/pdf\r\n\r\n%PDF-1.7\r\n"
+        ));
+        assert!(body.contains(
+            "Content-Disposition: form-data; name=\"Attachments\"; filename=\"notes.txt\"\r\n\r\nnotes\r\n"
+        ));
+    }
+}
+
+mod negative_constants {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Ledger,
+        params: { port: u16 },
+        post adjust("http://127.0.0.1:$$port/adjust") {
+            json {
+                Account: string = $account,
+                Delta: int = -1,
+                Rate: float = -0.5,
+            }
+        } -> {
+            json {
+                Balance: int,
+                Floor: int = -100,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn negative_constants_are_sent_and_defaulted() {
+        let (server, client) = common::serve(vec![common::json(r#"{"Balance":-7}"#)], |port| {
+            Ledger::new(LedgerOptions { port })
+        });
+        let result = client.adjust("main").await.unwrap();
+        assert_eq!(result.balance, -7);
+        assert_eq!(result.floor, -100);
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Account": "main", "Delta": -1, "Rate": -0.5 })
+        );
+    }
+}
+
+mod optional_variables {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Profiles,
+        params: { port: u16 },
+        post update("http://127.0.0.1:$$port/profile") {
+            json {
+                Name: string = $name,
+                Nick?: string = $nick,
+                Age: uint = $age? || 18,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn optional_variables_are_option_arguments() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Profiles::new(ProfilesOptions { port })
+        });
+
+        let nick: Option<String> = Some("al".to_owned());
+        let age: Option<u64> = Some(30);
+        client.update("alice", nick, age).await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Name": "alice", "Nick": "al", "Age": 30 })
+        );
+
+        client.update("bob", None, None).await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Name": "bob", "Age": 18 })
+        );
+    }
+}
+
+mod readonly_fields {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Notes,
+        params: { port: u16 },
+        templates: {
+            note {
+                Id: uint readonly,
+                Title: string,
+            },
+        }
+        post create("http://127.0.0.1:$$port/notes") {
+            json: note { Title: string = $title }
+        } -> {
+            json: note {}
+        }
+    }
+
+    #[tokio::test]
+    async fn readonly_field_is_not_sent() {
+        let (server, client) =
+            common::serve(vec![common::json(r#"{"Id":7,"Title":"todo"}"#)], |port| {
+                Notes::new(NotesOptions { port })
+            });
+        let note = client.create("todo").await.unwrap();
+        assert_eq!(note.id, 7);
+        assert_eq!(note.title, "todo");
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Title": "todo" })
+        );
+    }
+}
+
+mod struct_args {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Templates,
+        params: { port: u16 },
+        args: struct,
+        post add_template("http://127.0.0.1:$$port/templates") {
+            json {
+                Name: string = $name,
+                Content: string = $content,
+                Version: "1.0",
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn method_takes_the_request_data() {
+        let (server, client) = common::serve(vec![common::ok()], |port| {
+            Templates::new(TemplatesOptions { port })
+        });
+        let data = AddTemplateRequestData {
+            name: "welcome".to_owned(),
+            content: "hi ${name}".to_owned(),
+            ..Default::default()
+        };
+        client.add_template(data).await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Name": "welcome", "Content": "hi ${name}", "Version": "1.0" })
+        );
+    }
+}
+
+mod transfer {
+    use super::common;
+    use bytes::Bytes;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Upload,
+        params: { port: u16 },
+        put chunked("http://127.0.0.1:$$port/chunked") {
+            bytes = $payload
+            transfer: chunked
+        }
+        put length("http://127.0.0.1:$$port/length") {
+            bytes = $payload
+            transfer: length
+        }
+    }
+
+    fn client(server: &MockServer) -> Upload {
+        Upload::new(UploadOptions { port: server.port })
+    }
+
+    #[tokio::test]
+    async fn chunked_body_has_no_length() {
+        let server = MockServer::start(vec![common::ok()]);
+        client(&server)
+            .chunked(Bytes::from_static(b"some bytes"))
+            .await
+            .unwrap();
+        let request = server.request();
+        assert!(request.chunked);
+        assert_eq!(request.header("content-length"), None);
+        assert_eq!(request.body, b"some bytes");
+    }
+
+    #[tokio::test]
+    async fn length_body_is_not_chunked() {
+        let server = MockServer::start(vec![common::ok()]);
+        client(&server)
+            .length(Bytes::from_static(b"some bytes"))
+            .await
+            .unwrap();
+        let request = server.request();
+        assert!(!request.chunked);
+        assert_eq!(request.header_values("content-length"), ["10"]);
+        assert_eq!(request.body, b"some bytes");
+    }
+}
+
+mod urlencoded_encoding {
+    use super::common;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Encoding,
+        params: { port: u16 },
+        post form("http://127.0.0.1:$$port/form") {
+            urlencoded {
+                Text: string = $text,
+            }
+        }
+        post percent("http://127.0.0.1:$$port/percent") {
+            urlencoded(rfc3986) {
+                Text: string = $text,
+            }
+        }
+        post declared("http://127.0.0.1:$$port/declared") {
+            header {
+                "Content-Type" = "application/x-www-form-urlencoded; charset=utf-8",
+            }
+            urlencoded(rfc3986) {
+                Text: string = $text,
+            }
+        }
+    }
+
+    fn client(server: &MockServer) -> Encoding {
+        Encoding::new(EncodingOptions { port: server.port })
+    }
+
+    #[tokio::test]
+    async fn form_encodes_spaces_as_plus() {
+        let server = MockServer::start(vec![common::ok()]);
+        client(&server).form("a b*~".to_owned()).await.unwrap();
+        let request = server.request();
+        assert_eq!(request.body_text(), "Text=a+b*%7E");
+        assert_eq!(
+            request.header_values("content-type"),
+            ["application/x-www-form-urlencoded"]
+        );
+    }
+
+    #[tokio::test]
+    async fn rfc3986_encodes_spaces_as_percent() {
+        let server = MockServer::start(vec![common::ok()]);
+        client(&server).percent("a b*~".to_owned()).await.unwrap();
+        let request = server.request();
+        assert_eq!(request.body_text(), "Text=a%20b%2A~");
+        assert_eq!(
+            request.header_values("content-type"),
+            ["application/x-www-form-urlencoded"]
+        );
+    }
+
+    #[tokio::test]
+    async fn rfc3986_keeps_a_declared_content_type() {
+        let server = MockServer::start(vec![common::ok()]);
+        client(&server).declared("a b".to_owned()).await.unwrap();
+        let request = server.request();
+        assert_eq!(request.body_text(), "Text=a%20b");
+        assert_eq!(
+            request.header_values("content-type"),
+            ["application/x-www-form-urlencoded; charset=utf-8"]
+        );
+    }
+}
+
+mod urlencoded_nested {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sign,
+        params: { port: u16 },
+        post add_sign("http://127.0.0.1:$$port/") {
+            urlencoded {
+                SignName: string = $sign_name,
+                SignFileList {
+                    FileSuffix: string,
+                    FileContents: string,
+                }[] = $sign_file_list,
+                Remark: string = $remark,
+                Tags: string[] = $tags,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn nested_values_follow_the_declared_order() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Sign::new(SignOptions { port }));
+        client
+            .add_sign(
+                "shop".to_owned(),
+                vec![
+                    AddSignRequestDataSignFileList {
+                        file_suffix: "jpg".to_owned(),
+                        file_contents: "a b".to_owned(),
+                    },
+                    AddSignRequestDataSignFileList {
+                        file_suffix: "png".to_owned(),
+                        file_contents: "c".to_owned(),
+                    },
+                ],
+                "note".to_owned(),
+                vec!["z".to_owned(), "y".to_owned()],
+            )
+            .await
+            .unwrap();
+        let request = server.request();
+        assert_eq!(
+            request.body_text(),
+            "SignName=shop\
+             &SignFileList.1.FileSuffix=jpg&SignFileList.1.FileContents=a+b\
+             &SignFileList.2.FileSuffix=png&SignFileList.2.FileContents=c\
+             &Remark=note&Tags.1=z&Tags.2=y"
+        );
+        assert_eq!(
+            request.header("content-type"),
+            Some("application/x-www-form-urlencoded")
+        );
+    }
+}
+
+mod validate {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Orders,
+        params: { port: u16 },
+        post create("http://127.0.0.1:$$port/orders") {
+            json {
+                Count: uint(1..=10),
+                Ratio?: float(0.0..=1.0),
+                Lines: {
+                    Qty: uint(1..=99),
+                }[],
+            } = $order
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_reports_the_first_failure() {
+        let mut order = CreateRequestData {
+            count: 3,
+            ratio: None,
+            lines: vec![CreateRequestDataLines { qty: 1 }],
+        };
+        assert!(order.validate().is_ok());
+
+        order.ratio = Some(1.5);
+        let err = order.validate().unwrap_err();
+        assert_eq!(err.field, "Ratio");
+        assert_eq!(err.message, "1.5 is out of 0.0..=1.0");
+
+        order.ratio = Some(0.5);
+        order.lines.push(CreateRequestDataLines { qty: 100 });
+        let err = order.validate().unwrap_err();
+        assert_eq!(err.field, "Qty");
+
+        // the method validates before sending
+        let (server, client) = common::serve(vec![common::ok()], |port| {
+            Orders::new(OrdersOptions { port })
+        });
+        order.lines.pop();
+        order.count = 0;
+        let result = client.create(order).await;
+        assert!(matches!(
+            result,
+            Err(OrdersError::InvalidArgument { field: "Count", .. })
+        ));
+        assert!(server.no_more_requests());
+    }
+}
+
+mod writeonly_fields {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Accounts,
+        params: { port: u16 },
+        templates: {
+            account {
+                Name: string,
+                Password: string writeonly,
+            },
+        }
+        post create("http://127.0.0.1:$$port/accounts") {
+            json: account { Name: string = $name, Password: string = $password }
+        } -> {
+            json: account {}
+        }
+    }
+
+    #[tokio::test]
+    async fn writeonly_field_is_not_decoded() {
+        let (server, client) = common::serve(
+            vec![common::json(r#"{"Name":"ann","Password":"******"}"#)],
+            |port| Accounts::new(AccountsOptions { port }),
+        );
+        let account = client.create("ann", "hunter2").await.unwrap();
+        assert_eq!(account.name, "ann");
+        assert_eq!(account.password, "");
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Name": "ann", "Password": "hunter2" })
+        );
+    }
+}
+
+mod field_order {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Signed,
+        params: { port: u16 },
+        field_order: sorted,
+        post submit("http://127.0.0.1:$$port/submit") {
+            query {
+                Zeta: string = $zeta,
+                Alpha: "a",
+            }
+            json {
+                Nonce: string = $nonce,
+                Amount: uint = $amount,
+                Currency: "CNY",
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn fields_are_sent_sorted_by_name() {
+        let (server, client) = common::serve(vec![common::ok()], |port| {
+            Signed::new(SignedOptions { port })
+        });
+        client.submit("z", 5, "n1").await.unwrap();
+        let request = server.request();
+        assert_eq!(request.target, "/submit?Alpha=a&Zeta=z");
+        assert_eq!(
+            request.body_text(),
+            r#"{"Amount":5,"Currency":"CNY","Nonce":"n1"}"#
+        );
+    }
+}
+
+mod emit_tests {
+    use power_reqwest::reqwest;
+
+    // the generated smoke tests run with the others in this file
+    reqwest! {
+        name: Sms,
+        emit_tests: true,
+        post send("http://127.0.0.1/send") {
+            query { Action: "SendSms" }
+            json {
+                Phone: string example = "123",
+                Tags: string[],
+            } = $data
+        }
+        post sign("http://127.0.0.1/sign") {
+            urlencoded { Name: string = $name }
+        }
+    }
+
+    #[test]
+    fn smoke_tests_are_generated() {
+        send_request_serializes();
+        sign_request_serializes();
+    }
+}
+
+mod empty_lists {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Tags,
+        params: { port: u16 },
+        empty_lists: skip,
+        post save("http://127.0.0.1:$$port/save") {
+            json {
+                Name: string = $name,
+                Tags: string[] = $tags,
+            }
+        } -> {
+            json { Tags: string[] }
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_lists_are_left_out() {
+        let (server, client) = common::serve(
+            vec![
+                common::json(r#"{"Tags":[]}"#),
+                common::json(r#"{"Tags":[]}"#),
+            ],
+            |port| Tags::new(TagsOptions { port }),
+        );
+        // responses are decoded as usual
+        let data = client.save("a", vec![]).await.unwrap();
+        assert!(data.tags.is_empty());
+        assert_eq!(server.request().json(), serde_json::json!({ "Name": "a" }));
+        client.save("b", vec!["x".to_owned()]).await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Name": "b", "Tags": ["x"] })
+        );
+    }
+}
+
+mod optional_body {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Jobs,
+        params: { port: u16 },
+        post run("http://127.0.0.1:$$port/run") {
+            json? { Priority: uint } = $options
+        }
+    }
+
+    #[tokio::test]
+    async fn none_sends_no_body() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Jobs::new(JobsOptions { port })
+        });
+        client.run(None).await.unwrap();
+        let request = server.request();
+        assert!(request.body.is_empty());
+        assert_eq!(request.header("content-type"), None);
+
+        client
+            .run(Some(RunRequestData { priority: 3 }))
+            .await
+            .unwrap();
+        let request = server.request();
+        assert_eq!(request.header("content-type"), Some("application/json"));
+        assert_eq!(request.json(), serde_json::json!({ "Priority": 3 }));
+    }
+}
+
+mod string_transforms {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Accounts,
+        params: { port: u16 },
+        post upper("http://127.0.0.1:$$port/upper") {
+            json { Code: string = upper($code) }
+        }
+        post lower("http://127.0.0.1:$$port/lower") {
+            json { Email: string = lower($email) }
+        }
+        post trim("http://127.0.0.1:$$port/trim") {
+            query { Name: string = trim($name) }
+        }
+    }
+
+    fn client() -> (common::MockServer, Accounts) {
+        common::serve(vec![common::ok()], |port| {
+            Accounts::new(AccountsOptions { port })
+        })
+    }
+
+    #[tokio::test]
+    async fn upper() {
+        let (server, client) = client();
+        client.upper("cn-Hz ß").await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Code": "CN-HZ SS" })
+        );
+    }
+
+    #[tokio::test]
+    async fn lower() {
+        let (server, client) = client();
+        client.lower("Ann@Example.COM").await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!({ "Email": "ann@example.com" })
+        );
+    }
+
+    #[tokio::test]
+    async fn trim() {
+        let (server, client) = client();
+        client.trim("\t ann lee \n").await.unwrap();
+        assert_eq!(server.request().target, "/trim?Name=ann+lee");
+    }
+}
+
+mod array_body {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Batch,
+        params: { port: u16 },
+        post upload("http://127.0.0.1:$$port/items") {
+            json [{ Id: uint, Name: string }] = $items
+        }
+    }
+
+    #[tokio::test]
+    async fn the_body_is_a_top_level_array() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Batch::new(BatchOptions { port })
+        });
+        let items: Vec<UploadRequestData> = vec![
+            UploadRequestData {
+                id: 1,
+                name: "a".to_owned(),
+            },
+            UploadRequestData {
+                id: 2,
+                name: "b".to_owned(),
+            },
+        ];
+        client.upload(items).await.unwrap();
+        assert_eq!(
+            server.request().json(),
+            serde_json::json!([{ "Id": 1, "Name": "a" }, { "Id": 2, "Name": "b" }])
+        );
+        client.upload(vec![]).await.unwrap();
+        assert_eq!(server.request().json(), serde_json::json!([]));
+    }
+}
@@ -0,0 +1,815 @@
+// decoding responses and their errors
+mod common;
+
+mod empty_struct {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    // without `strict: true` an empty body is a valid declaration
+    reqwest! {
+        name: Lenient,
+        params: { port: u16 },
+        post ping("http://127.0.0.1:$$port/ping") {
+            urlencoded {}
+        } -> {
+            json {}
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_structs_are_allowed_by_default() {
+        let (server, client) = common::serve(vec![common::json(r#"{"Ignored":true}"#)], |port| {
+            Lenient::new(LenientOptions { port })
+        });
+        let PingResponseData {} = client.ping().await.unwrap();
+        let request = server.request();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.body_text(), "");
+    }
+}
+
+mod enum_unknown {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16 },
+        post send("http://127.0.0.1:$$port/send") -> {
+            json {
+                Code: enum("OK", "isv.BUSINESS_LIMIT"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn unlisted_code_is_unknown() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Code":"isv.BUSINESS_LIMIT"}"#),
+                common::json(r#"{"Code":"isv.NEW_CODE"}"#),
+            ],
+            |port| Sms::new(SmsOptions { port }),
+        );
+
+        let result = client.send().await.unwrap();
+        assert!(matches!(
+            result.code,
+            SendResponseDataCode::IsvBusinessLimit
+        ));
+        assert_eq!(result.code.as_str(), "isv.BUSINESS_LIMIT");
+
+        let result = client.send().await.unwrap();
+        assert!(
+            matches!(&result.code, SendResponseDataCode::Unknown(code) if code == "isv.NEW_CODE")
+        );
+        assert_eq!(result.code.to_string(), "isv.NEW_CODE");
+    }
+}
+
+mod enums {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Chat,
+        json_case: PascalCase,
+        params: { port: u16 },
+        get last_message("http://127.0.0.1:$$port/messages/last") -> {
+            json {
+                Body: enum("text" { content: string, font_size: uint }, "deleted"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn struct_variant_fields_follow_json_case() {
+        let (_server, client) = common::serve(
+            vec![common::json(
+                r#"{"Body":{"text":{"Content":"hi","FontSize":12}}}"#,
+            )],
+            |port| Chat::new(ChatOptions { port }),
+        );
+        let message = client.last_message().await.unwrap();
+        assert_eq!(
+            message.body,
+            LastMessageResponseDataBody::Text {
+                content: "hi".to_owned(),
+                font_size: 12,
+            }
+        );
+    }
+
+    #[test]
+    fn unit_variants_stay_plain_codes() {
+        let body: LastMessageResponseDataBody = serde_json::from_str(r#""deleted""#).unwrap();
+        assert_eq!(body, LastMessageResponseDataBody::Deleted);
+        assert_eq!(
+            serde_json::to_string(&LastMessageResponseDataBody::Text {
+                content: "hi".to_owned(),
+                font_size: 12,
+            })
+            .unwrap(),
+            r#"{"text":{"Content":"hi","FontSize":12}}"#
+        );
+    }
+}
+
+mod field_attributes {
+    use super::common;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Users,
+        params: { port: u16 },
+        get user("http://127.0.0.1:$$port/user") -> {
+            json {
+                #![serde(deny_unknown_fields)]
+                Email: string #[serde(alias = "Mail")],
+            }
+        }
+    }
+
+    fn client(server: &MockServer) -> Users {
+        Users::new(UsersOptions { port: server.port })
+    }
+
+    #[tokio::test]
+    async fn field_attribute_is_applied() {
+        let server = MockServer::start(vec![common::json(r#"{"Mail":"a@b.c"}"#)]);
+        let user = client(&server).user().await.unwrap();
+        assert_eq!(user.email, "a@b.c");
+    }
+
+    #[tokio::test]
+    async fn struct_attribute_is_applied() {
+        let server = MockServer::start(vec![common::json(r#"{"Email":"a@b.c","Age":3}"#)]);
+        let result = client(&server).user().await;
+        assert!(matches!(result, Err(UsersError::Decode { .. })));
+    }
+}
+
+mod form_response {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Legacy,
+        params: { port: u16 },
+        get token("http://127.0.0.1:$$port/token") -> {
+            form {
+                AccessToken: string,
+                ExpiresIn: uint,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn form_body_is_decoded() {
+        let (_server, client) = common::serve(
+            vec![common::response(
+                "200 OK",
+                &[("content-type", "application/x-www-form-urlencoded")],
+                "AccessToken=a%20b%2Bc&ExpiresIn=3600",
+            )],
+            |port| Legacy::new(LegacyOptions { port }),
+        );
+        let token = client.token().await.unwrap();
+        assert_eq!(token.access_token, "a b+c");
+        assert_eq!(token.expires_in, 3600);
+    }
+
+    #[tokio::test]
+    async fn invalid_form_fails_to_decode() {
+        let (_server, client) = common::serve(
+            vec![common::response(
+                "200 OK",
+                &[],
+                "AccessToken=x&ExpiresIn=soon",
+            )],
+            |port| Legacy::new(LegacyOptions { port }),
+        );
+        let result = client.token().await;
+        assert!(matches!(result, Err(LegacyError::Decode { .. })));
+    }
+}
+
+mod int_or_str {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Orders,
+        params: { port: u16 },
+        get order("http://127.0.0.1:$$port/order") -> {
+            json {
+                Status: uint int_or_str,
+                Level?: int int_or_str,
+                State: enum("1", "2") int_or_str,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn numbers_and_strings_decode_alike() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Status":1,"Level":-2,"State":2}"#),
+                common::json(r#"{"Status":"1","Level":"-2","State":"2"}"#),
+                common::json(r#"{"Status":"x","State":"1"}"#),
+            ],
+            |port| Orders::new(OrdersOptions { port }),
+        );
+
+        for _ in 0..2 {
+            let order = client.order().await.unwrap();
+            assert_eq!(order.status, 1);
+            assert_eq!(order.level, Some(-2));
+            assert!(matches!(order.state, OrderResponseDataState::V2));
+            assert_eq!(order.state.as_str(), "2");
+        }
+
+        let result = client.order().await;
+        assert!(matches!(result, Err(OrdersError::Decode { .. })));
+    }
+}
+
+mod json_paths {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Auth,
+        params: { port: u16 },
+        post token("http://127.0.0.1:$$port/token") -> {
+            json {
+                Code: uint,
+                token: string = $.data.access_token,
+                first_scope?: string = $.data."scope-list"[0],
+                expires: uint = $.data.expires_in || 7200,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn nested_values_land_in_flat_fields() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(
+                    r#"{"Code":0,"data":{"access_token":"t1","scope-list":["read","write"],"expires_in":60}}"#,
+                ),
+                common::json(r#"{"Code":0,"data":{"access_token":"t2"}}"#),
+                common::json(r#"{"Code":1,"data":{}}"#),
+            ],
+            |port| Auth::new(AuthOptions { port }),
+        );
+
+        let result = client.token().await.unwrap();
+        assert_eq!(result.code, 0);
+        assert_eq!(result.token, "t1");
+        assert_eq!(result.first_scope.as_deref(), Some("read"));
+        assert_eq!(result.expires, 60);
+
+        let result = client.token().await.unwrap();
+        assert_eq!(result.token, "t2");
+        assert_eq!(result.first_scope, None);
+        assert_eq!(result.expires, 7200);
+
+        let result = client.token().await;
+        assert!(matches!(result, Err(AuthError::Decode { .. })));
+    }
+}
+
+mod optional_response {
+    use super::common;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Optional,
+        params: { port: u16 },
+        get item("http://127.0.0.1:$$port/item") -> ?{
+            json { Name: string }
+        }
+        get note("http://127.0.0.1:$$port/note") -> ?{ text }
+    }
+
+    fn client(server: &MockServer) -> Optional {
+        Optional::new(OptionalOptions { port: server.port })
+    }
+
+    #[tokio::test]
+    async fn no_content_is_none() {
+        let server = MockServer::start(vec![common::response("204 No Content", &[], "")]);
+        let item = client(&server).item().await.unwrap();
+        assert!(item.is_none());
+        let request = server.request();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.target, "/item");
+    }
+
+    #[tokio::test]
+    async fn chunked_empty_body_is_none() {
+        let server = MockServer::start(vec![common::raw(
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-type: application/json\r\n\
+             transfer-encoding: chunked\r\n\r\n0\r\n\r\n",
+        )]);
+        let item = client(&server).item().await.unwrap();
+        assert!(item.is_none());
+    }
+
+    #[tokio::test]
+    async fn blank_body_is_none() {
+        let server = MockServer::start(vec![common::json(" \r\n")]);
+        let item = client(&server).item().await.unwrap();
+        assert!(item.is_none());
+    }
+
+    #[tokio::test]
+    async fn body_is_decoded() {
+        let server = MockServer::start(vec![common::raw(
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-type: application/json\r\n\
+             transfer-encoding: chunked\r\n\r\ne\r\n{\"Name\":\"pen\"}\r\n0\r\n\r\n",
+        )]);
+        let item = client(&server).item().await.unwrap();
+        assert_eq!(item.unwrap().name, "pen");
+    }
+
+    #[tokio::test]
+    async fn invalid_body_fails_to_decode() {
+        let server = MockServer::start(vec![common::json("{\"Name\":")]);
+        let result = client(&server).item().await;
+        assert!(matches!(result, Err(OptionalError::Decode { .. })));
+    }
+
+    #[tokio::test]
+    async fn text_body() {
+        let server = MockServer::start(vec![
+            common::response("200 OK", &[], ""),
+            common::response("200 OK", &[], "hello"),
+        ]);
+        let client = client(&server);
+        assert_eq!(client.note().await.unwrap(), None);
+        assert_eq!(client.note().await.unwrap().as_deref(), Some("hello"));
+    }
+}
+
+mod rate_limit_info {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Api,
+        params: { port: u16 },
+        get status("http://127.0.0.1:$$port/status") -> {
+            text
+            rate_limit_info
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limit_headers_are_parsed() {
+        let (_server, client) = common::serve(
+            vec![
+                common::response(
+                    "200 OK",
+                    &[
+                        ("X-RateLimit-Remaining", "42"),
+                        ("X-RateLimit-Reset", "1700000000"),
+                    ],
+                    "a",
+                ),
+                common::response(
+                    "200 OK",
+                    &[("RateLimit-Remaining", "5"), ("RateLimit-Reset", "30")],
+                    "b",
+                ),
+                common::response("200 OK", &[("X-RateLimit-Remaining", "1")], "c"),
+            ],
+            |port| Api::new(ApiOptions { port }),
+        );
+
+        let (text, info) = client.status().await.unwrap();
+        assert_eq!(text, "a");
+        let info = info.unwrap();
+        assert_eq!(info.remaining, 42);
+        assert_eq!(info.reset.timestamp(), 1_700_000_000);
+
+        // small reset values count seconds from now
+        let (_, info) = client.status().await.unwrap();
+        let info = info.unwrap();
+        assert_eq!(info.remaining, 5);
+        let in_seconds = (info.reset - chrono::Utc::now()).num_seconds();
+        assert!((25..=30).contains(&in_seconds), "{in_seconds}");
+
+        let (_, info) = client.status().await.unwrap();
+        assert!(info.is_none());
+    }
+}
+
+mod response_defaults {
+    use super::common;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Stock,
+        params: { port: u16 },
+        get item("http://127.0.0.1:$$port/item") -> {
+            json {
+                Name: string,
+                Count: uint = 5,
+                Unit: string = "piece",
+            }
+        }
+    }
+
+    fn client(server: &MockServer) -> Stock {
+        Stock::new(StockOptions { port: server.port })
+    }
+
+    #[tokio::test]
+    async fn missing_fields_get_their_defaults() {
+        let server = MockServer::start(vec![common::json(r#"{"Name":"pen"}"#)]);
+        let item = client(&server).item().await.unwrap();
+        assert_eq!(item.name, "pen");
+        assert_eq!(item.count, 5);
+        assert_eq!(item.unit, "piece");
+    }
+
+    #[tokio::test]
+    async fn present_fields_are_decoded() {
+        let server = MockServer::start(vec![common::json(
+            r#"{"Name":"pen","Count":2,"Unit":"box"}"#,
+        )]);
+        let item = client(&server).item().await.unwrap();
+        assert_eq!(item.count, 2);
+        assert_eq!(item.unit, "box");
+    }
+}
+
+mod success_status {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Jobs,
+        params: { port: u16 },
+        post submit("http://127.0.0.1:$$port/jobs") -> {
+            text
+            success_status: [200, 202]
+        }
+    }
+
+    #[tokio::test]
+    async fn listed_codes_are_success() {
+        let (_server, client) = common::serve(
+            vec![
+                common::response("202 Accepted", &[], "queued"),
+                common::response("201 Created", &[], "created"),
+            ],
+            |port| Jobs::new(JobsOptions { port }),
+        );
+
+        assert_eq!(client.submit().await.unwrap(), "queued");
+
+        // 2xx codes that are not listed fail
+        let result = client.submit().await;
+        assert!(matches!(
+            result,
+            Err(JobsError::Status { status, .. }) if status.as_u16() == 201
+        ));
+        assert!(Jobs::submit_is_success(reqwest::StatusCode::ACCEPTED));
+        assert!(!Jobs::submit_is_success(reqwest::StatusCode::NO_CONTENT));
+    }
+}
+
+mod capture_body_on_error {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Profiles,
+        params: { port: u16 },
+        capture_body_on_error: true,
+        get profile("http://127.0.0.1:$$port/profile") -> {
+            json { Age: uint }
+        }
+    }
+
+    #[tokio::test]
+    async fn errors_carry_the_raw_body() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Age":"ten"}"#),
+                common::response("500 Internal Server Error", &[], "db down"),
+            ],
+            |port| Profiles::new(ProfilesOptions { port }),
+        );
+        match client.profile().await {
+            Err(ProfilesError::Decode { body, .. }) => {
+                assert_eq!(body.as_deref(), Some(r#"{"Age":"ten"}"#))
+            }
+            _ => panic!("expect a decode error"),
+        }
+        match client.profile().await {
+            Err(ProfilesError::Status { body, .. }) => assert_eq!(body.as_deref(), Some("db down")),
+            _ => panic!("expect a status error"),
+        }
+    }
+}
+
+mod text_response {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Motd,
+        params: { port: u16 },
+        get today("http://127.0.0.1:$$port/motd") -> { text }
+    }
+
+    #[tokio::test]
+    async fn the_body_is_returned_as_a_string() {
+        let (_server, client) = common::serve(
+            vec![common::response(
+                "200 OK",
+                &[("content-type", "text/plain")],
+                "hello\nworld",
+            )],
+            |port| Motd::new(MotdOptions { port }),
+        );
+        let text: String = client.today().await.unwrap();
+        assert_eq!(text, "hello\nworld");
+    }
+}
+
+mod decompress {
+    use super::common;
+    use power_reqwest::reqwest;
+    use std::io::Write;
+
+    reqwest! {
+        name: Auto,
+        params: { port: u16 },
+        decompress: auto,
+        get account("http://127.0.0.1:$$port/account") -> {
+            json { Age: uint }
+        }
+    }
+
+    reqwest! {
+        name: Manual,
+        params: { port: u16 },
+        decompress: manual,
+        capture_body_on_error: true,
+        get profile("http://127.0.0.1:$$port/profile") -> {
+            json { Age: uint }
+        }
+        get motd("http://127.0.0.1:$$port/motd") -> { text }
+    }
+
+    fn gzip(body: &str) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn deflate(body: &str) -> Vec<u8> {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(body.as_bytes()).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn brotli(body: &str) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        brotli::BrotliCompress(&mut body.as_bytes(), &mut compressed, &Default::default()).unwrap();
+        compressed
+    }
+
+    #[tokio::test]
+    async fn auto_is_decoded_by_reqwest() {
+        let (server, client) = common::serve(
+            vec![common::binary(
+                "200 OK",
+                &[("content-encoding", "gzip")],
+                &gzip(r#"{"Age":42}"#),
+            )],
+            |port| Auto::new(AutoOptions { port }),
+        );
+        assert_eq!(client.account().await.unwrap().age, 42);
+        assert!(server.request().header("accept-encoding").is_some());
+    }
+
+    #[tokio::test]
+    async fn manual_decodes_by_content_encoding() {
+        let (server, client) = common::serve(
+            vec![
+                common::binary(
+                    "200 OK",
+                    &[("content-encoding", "gzip")],
+                    &gzip(r#"{"Age":42}"#),
+                ),
+                common::binary(
+                    "200 OK",
+                    &[("content-encoding", "deflate")],
+                    &deflate(r#"{"Age":7}"#),
+                ),
+                common::binary(
+                    "200 OK",
+                    &[("content-encoding", "br")],
+                    &brotli(r#"{"Age":9}"#),
+                ),
+                common::binary("200 OK", &[("content-encoding", "gzip")], &gzip("hello")),
+            ],
+            |port| Manual::new(ManualOptions { port }),
+        );
+        assert_eq!(client.profile().await.unwrap().age, 42);
+        // reqwest doesn't ask for compressed bodies itself
+        assert_eq!(server.request().header("accept-encoding"), None);
+        assert_eq!(client.profile().await.unwrap().age, 7);
+        assert_eq!(client.profile().await.unwrap().age, 9);
+        assert_eq!(client.motd().await.unwrap(), "hello");
+    }
+
+    #[tokio::test]
+    async fn manual_recognizes_unlabelled_gzip() {
+        let (_server, client) = common::serve(
+            vec![
+                common::binary("200 OK", &[], &gzip(r#"{"Age":42}"#)),
+                common::json(r#"{"Age":7}"#),
+            ],
+            |port| Manual::new(ManualOptions { port }),
+        );
+        assert_eq!(client.profile().await.unwrap().age, 42);
+        assert_eq!(client.profile().await.unwrap().age, 7);
+    }
+
+    #[tokio::test]
+    async fn manual_decodes_error_bodies() {
+        let (_server, client) = common::serve(
+            vec![
+                common::binary(
+                    "500 Internal Server Error",
+                    &[("content-encoding", "gzip")],
+                    &gzip("db down"),
+                ),
+                common::binary("200 OK", &[("content-encoding", "gzip")], b"not gzip"),
+            ],
+            |port| Manual::new(ManualOptions { port }),
+        );
+        match client.profile().await {
+            Err(ManualError::Status { body, .. }) => assert_eq!(body.as_deref(), Some("db down")),
+            _ => panic!("expect a status error"),
+        }
+        assert!(matches!(
+            client.profile().await,
+            Err(ManualError::Decode { .. })
+        ));
+    }
+}
+
+mod input_keys {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Orders,
+        params: { port: u16 },
+        get order("http://127.0.0.1:$$port/order") -> {
+            json {
+                Date <- ["CreateDate", "CreatedAt"]: string,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn alternate_keys_are_accepted() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Date":"d1"}"#),
+                common::json(r#"{"CreateDate":"d2"}"#),
+                common::json(r#"{"CreatedAt":"d3"}"#),
+            ],
+            |port| Orders::new(OrdersOptions { port }),
+        );
+        assert_eq!(client.order().await.unwrap().date, "d1");
+        assert_eq!(client.order().await.unwrap().date, "d2");
+        assert_eq!(client.order().await.unwrap().date, "d3");
+    }
+}
+
+mod require {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Sms,
+        params: { port: u16 },
+        get send("http://127.0.0.1:$$port/send") -> {
+            json {
+                Code: string,
+                RequestId: string require,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn a_missing_field_is_named() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Code":"OK","RequestId":"r1"}"#),
+                common::json(r#"{"Code":"OK"}"#),
+                common::json(r#"{"Code":"OK","RequestId":null}"#),
+            ],
+            |port| Sms::new(SmsOptions { port }),
+        );
+        assert_eq!(client.send().await.unwrap().request_id, "r1");
+        for _ in 0..2 {
+            match client.send().await {
+                Err(SmsError::Decode { source, .. }) => {
+                    assert_eq!(source.to_string(), "missing required field `RequestId`")
+                }
+                _ => panic!("expect a decode error"),
+            }
+        }
+    }
+}
+
+mod response_arc {
+    use super::common;
+    use power_reqwest::reqwest;
+    use std::sync::Arc;
+
+    reqwest! {
+        name: Shared,
+        params: { port: u16 },
+        response_arc: true,
+        get config("http://127.0.0.1:$$port/config") -> {
+            json { Region: string }
+        }
+        get motd("http://127.0.0.1:$$port/motd") -> { text }
+    }
+
+    #[tokio::test]
+    async fn data_is_returned_in_an_arc() {
+        let (_server, client) = common::serve(
+            vec![common::json(r#"{"Region":"eu"}"#), common::ok()],
+            |port| Shared::new(SharedOptions { port }),
+        );
+        let config: Arc<ConfigResponseData> = client.config().await.unwrap();
+        let shared = Arc::clone(&config);
+        assert_eq!(shared.region, "eu");
+        // text is returned as is
+        let _: String = client.motd().await.unwrap();
+    }
+}
+
+mod envelope_templates {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Api,
+        params: { port: u16 },
+        templates: {
+            envelope {
+                Code: string,
+                Message: string,
+                Data: string,
+                TraceId: string,
+            },
+        }
+        get count("http://127.0.0.1:$$port/count") -> {
+            json: envelope {
+                Data: uint,
+                -TraceId,
+            }
+        }
+        get name("http://127.0.0.1:$$port/name") -> {
+            json: envelope {}
+        }
+    }
+
+    #[tokio::test]
+    async fn inherited_fields_can_be_redefined_and_removed() {
+        let (_server, client) = common::serve(
+            vec![
+                common::json(r#"{"Code":"OK","Message":"","Data":42}"#),
+                common::json(r#"{"Code":"OK","Message":"","Data":"ann","TraceId":"t1"}"#),
+            ],
+            |port| Api::new(ApiOptions { port }),
+        );
+        let CountResponseData {
+            code,
+            message: _,
+            data,
+        } = client.count().await.unwrap();
+        let data: u64 = data;
+        assert_eq!((code.as_str(), data), ("OK", 42));
+        let name = client.name().await.unwrap();
+        assert_eq!((name.data.as_str(), name.trace_id.as_str()), ("ann", "t1"));
+    }
+}
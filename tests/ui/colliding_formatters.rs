@@ -0,0 +1,15 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Events,
+    get list("http://127.0.0.1/events") -> {
+        json {
+            Bar {
+                Time: datetime("%Y-%m-%d"),
+            },
+            BarTime: datetime("%Y/%m/%d"),
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Events,
+    post create("http://127.0.0.1/events") {
+        json {
+            Meta: string = json($value),
+            At: uint = timestamp($value),
+        }
+    }
+}
+
+fn main() {}
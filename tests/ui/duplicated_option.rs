@@ -0,0 +1,11 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Twice,
+    empty_lists: skip,
+    strict: true,
+    empty_lists: keep,
+    get one("http://127.0.0.1/one")
+}
+
+fn main() {}
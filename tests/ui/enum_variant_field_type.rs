@@ -0,0 +1,13 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Chat,
+    json_case: PascalCase,
+    get last_message("http://127.0.0.1/messages/last") -> {
+        json {
+            Body: enum("text" { content: string, tags: string[] }),
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Docs,
+    post upload("http://127.0.0.1/docs") {
+        multipart {
+            Doc: file("pdf"),
+        } = $form
+    }
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Sms,
+    post send("http://127.0.0.1/send") {
+        json {
+            Text: string = format("{} {}", $name),
+        }
+    }
+}
+
+fn main() {}
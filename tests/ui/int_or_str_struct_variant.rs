@@ -0,0 +1,12 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Chat,
+    get last("http://127.0.0.1/last") -> {
+        json {
+            Body: enum("1" { text: string }, "2") int_or_str,
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+// the max_apis warning is denied to make it a failure
+#![deny(deprecated)]
+
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Big,
+    max_apis: 2,
+    get one("http://127.0.0.1/one")
+    get two("http://127.0.0.1/two")
+    get three("http://127.0.0.1/three")
+}
+
+fn main() {}
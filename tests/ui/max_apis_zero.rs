@@ -0,0 +1,9 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Empty,
+    max_apis: 0,
+    get one("http://127.0.0.1/one")
+}
+
+fn main() {}
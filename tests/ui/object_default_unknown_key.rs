@@ -0,0 +1,15 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Shop,
+    post create("http://127.0.0.1/items") {
+        json {
+            Size: {
+                Width: uint,
+                Height: uint,
+            } = { Width: 1, Depth: 2 },
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Shop,
+    post orders("http://127.0.0.1/orders") {
+        query { Page: uint = $page }
+        json { Status: string } = $filter
+    } -> {
+        json {
+            Total: uint,
+            Page: uint,
+            Size: uint,
+            Orders: { Id: uint }[],
+        }
+        page_info { total: Total, page: Page, size: Size, items: Orders, next: $page }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Search,
+    get search("http://127.0.0.1/search?q=$q") {
+        query { Q: string = $term }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Sms,
+    post send_sms("http://127.0.0.1/send") {
+        json {
+            PhoneNumber: string = $phone_number,
+            TemplateCode: string = $template_code,
+        }
+        key: [phone_number, template]
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Strict,
+    strict: true,
+    post send("http://127.0.0.1/send") {
+        urlencoded {}
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Upload,
+    post create("http://127.0.0.1/items") {
+        json { Name: string = $name }
+        transfer: chunked
+    }
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Typo,
+    strickt: true,
+    get one("http://127.0.0.1/one")
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Typo,
+    get one("http://127.0.0.1/one") -> {
+        json {
+            Id: uint readonyl,
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use power_reqwest::reqwest;
+
+reqwest! {
+    name: Sms,
+    validate_only: true,
+    post send_sms("http://127.0.0.1/send") {
+        json {
+            PhoneNumber: string = $phone_number,
+        }
+        key: [phone]
+    }
+}
+
+fn main() {}
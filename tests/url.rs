@@ -0,0 +1,245 @@
+// urls: path segments, query params and their encoding
+mod common;
+
+mod enum_query {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Shop,
+        params: { port: u16 },
+        get search("http://127.0.0.1:$$port/search") {
+            query {
+                Kind: enum("big box", "small") = $kind,
+                Dirs: enum("asc", "desc")[] = $dirs,
+            }
+        }
+        post filter("http://127.0.0.1:$$port/filter") {
+            form {
+                Kind: enum("big box", "small") = $kind,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn enums_are_sent_as_wire_strings() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Shop::new(ShopOptions { port })
+        });
+
+        client
+            .search(
+                SearchQueryKind::BigBox,
+                vec![SearchQueryDirs::Asc, SearchQueryDirs::Desc],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            server.request().target,
+            "/search?Kind=big+box&Dirs%5B%5D=asc&Dirs%5B%5D=desc"
+        );
+
+        client.filter(FilterRequestDataKind::Small).await.unwrap();
+        assert_eq!(server.request().body_text(), "Kind=small");
+    }
+}
+
+mod nested_query {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Search,
+        params: { port: u16 },
+        get search("http://127.0.0.1:$$port/search") {
+            query {
+                Sort: string = $sort,
+                Filter {
+                    Zeta: string,
+                    Alpha: uint,
+                } = $filter,
+                Tags: string[] = $tags,
+                Page: uint = $page,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn nested_values_follow_the_declared_order() {
+        let (server, client) = common::serve(vec![common::ok()], |port| {
+            Search::new(SearchOptions { port })
+        });
+        client
+            .search(
+                "new".to_owned(),
+                SearchQueryFilter {
+                    zeta: "z".to_owned(),
+                    alpha: 1,
+                },
+                vec!["b".to_owned(), "a".to_owned()],
+                2,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            server.request().target,
+            "/search?Sort=new&Filter%5BZeta%5D=z&Filter%5BAlpha%5D=1&Tags%5B%5D=b&Tags%5B%5D=a&Page=2"
+        );
+    }
+}
+
+mod optional_query {
+    use super::common;
+    use common::MockServer;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Feed,
+        params: { port: u16 },
+        get list("http://127.0.0.1:$$port/feed") {
+            query {
+                Limit: uint = $limit,
+                Cursor?: string = $cursor,
+            }
+        } -> {
+            json { Items: string[] }
+        }
+    }
+
+    fn client(server: &MockServer) -> Feed {
+        Feed::new(FeedOptions { port: server.port })
+    }
+
+    #[tokio::test]
+    async fn none_is_left_out_of_the_query() {
+        let server = MockServer::start(vec![common::json(r#"{"Items":["a"]}"#)]);
+        let feed = client(&server).list(10, None).await.unwrap();
+        assert_eq!(feed.items, ["a"]);
+        assert_eq!(server.request().target, "/feed?Limit=10");
+    }
+
+    #[tokio::test]
+    async fn some_is_sent() {
+        let server = MockServer::start(vec![common::json(r#"{"Items":[]}"#)]);
+        let feed = client(&server)
+            .list(10, Some("next".to_owned()))
+            .await
+            .unwrap();
+        assert!(feed.items.is_empty());
+        assert_eq!(server.request().target, "/feed?Limit=10&Cursor=next");
+    }
+}
+
+mod typed_variables {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Items,
+        params: { port: u16 },
+        get item("http://127.0.0.1:$$port/items/${id:u64}?rate=${rate: f64}")
+    }
+
+    #[tokio::test]
+    async fn typed_url_variables_keep_their_type() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Items::new(ItemsOptions { port }));
+        let id: u64 = 42;
+        client.item(id, 0.5).await.unwrap();
+        assert_eq!(server.request().target, "/items/42?rate=0.5");
+    }
+}
+
+mod special_characters {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Files,
+        params: { port: u16 },
+        get file("http://127.0.0.1:$$port/files/$name?q=$q")
+    }
+
+    #[tokio::test]
+    async fn variables_are_percent_encoded() {
+        let (server, client) =
+            common::serve(vec![common::ok()], |port| Files::new(FilesOptions { port }));
+        client.file("a b/c?d#e%", "x&y=z #1+ü").await.unwrap();
+        assert_eq!(
+            server.request().target,
+            "/files/a%20b%2Fc%3Fd%23e%25?q=x%26y%3Dz+%231%2B%C3%BC"
+        );
+    }
+}
+
+mod percent_encoded_literal {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Finder,
+        params: { port: u16 },
+        get search("http://127.0.0.1:$$port/search?q=a%20b&tag%2B=c%26d")
+    }
+
+    #[tokio::test]
+    async fn encoded_query_literals_are_decoded_once() {
+        let (server, client) = common::serve(vec![common::ok()], |port| {
+            Finder::new(FinderOptions { port })
+        });
+        client.search().await.unwrap();
+        assert_eq!(server.request().target, "/search?q=a+b&tag%2B=c%26d");
+    }
+}
+
+mod braceless_api {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Catalog,
+        params: { port: u16 },
+        get list("http://127.0.0.1:$$port/items?page=$page")
+        get count("http://127.0.0.1:$$port/items/count")
+    }
+
+    #[tokio::test]
+    async fn the_url_is_the_whole_request() {
+        let (server, client) = common::serve(vec![common::ok(), common::ok()], |port| {
+            Catalog::new(CatalogOptions { port })
+        });
+        client.list("2").await.unwrap();
+        assert_eq!(server.request().target, "/items?page=2");
+        client.count().await.unwrap();
+        let request = server.request();
+        assert_eq!(request.target, "/items/count");
+        assert!(request.body.is_empty());
+    }
+}
+
+mod mixed_query {
+    use super::common;
+    use power_reqwest::reqwest;
+
+    reqwest! {
+        name: Search,
+        params: { port: u16 },
+        get search("http://127.0.0.1:$$port/search?lang=en&page=$page") {
+            query { Q: string = $q }
+        }
+    }
+
+    #[tokio::test]
+    async fn url_and_block_params_share_one_query() {
+        let (server, client) = common::serve(vec![common::ok()], |port| {
+            Search::new(SearchOptions { port })
+        });
+        client.search("2", "rust").await.unwrap();
+        assert_eq!(server.request().target, "/search?lang=en&page=2&Q=rust");
+        let _query = SearchQuery {
+            q: "rust".to_owned(),
+            lang: "en".to_owned(),
+            page: "2".to_owned(),
+        };
+    }
+}